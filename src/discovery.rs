@@ -0,0 +1,185 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+};
+
+/// Paths commonly exposed by frameworks and deployments without being
+/// documented, e.g. administration, monitoring or leftovers of the build
+const HIDDEN_PATHS: &[&str] = &[
+    "admin",
+    "administrator",
+    "debug",
+    "console",
+    "actuator",
+    "actuator/env",
+    "actuator/heapdump",
+    "metrics",
+    "health",
+    "status",
+    "server-status",
+    "env",
+    ".env",
+    ".git/HEAD",
+    ".git/config",
+    "graphql",
+    "openapi.json",
+    "swagger.json",
+    "api-docs",
+];
+
+/// Prepended to documented paths to find internal or other versions of them
+const PREFIXES: &[&str] = &[
+    "admin/",
+    "internal/",
+    "private/",
+    "debug/",
+    "api/",
+    "v1/",
+    "v2/",
+    "beta/",
+];
+
+/// Appended to documented paths to find other representations, backups or
+/// sub-resources of them
+const SUFFIXES: &[&str] = &[
+    ".json",
+    ".xml",
+    ".bak",
+    ".old",
+    "~",
+    "/admin",
+    "/debug",
+    "/internal",
+    "/export",
+];
+
+/// Statuses with which servers refuse unknown paths and methods
+const REJECTION_STATUS_CODES: [u16; 3] = [404, 405, 501];
+
+/// Part of the API which is not documented in the specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Surface {
+    Method,
+    Path,
+}
+
+impl Display for Surface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Surface::Method => f.write_str("undocumented method"),
+            Surface::Path => f.write_str("undocumented path"),
+        }
+    }
+}
+
+/// Request looking for an undocumented part of the API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    /// Path relative to the url of the API, with the parameters of the
+    /// documented path in braces
+    pub path: String,
+    pub method: &'static str,
+    pub surface: Surface,
+}
+
+/// Probes the methods which are not declared for the path. `OPTIONS` and
+/// `HEAD` of paths declaring `GET` are left out, as HTTP servers answer them
+/// on their own.
+pub fn method_probes(path: &str, declared: &[&'static str]) -> Vec<Probe> {
+    [
+        "GET", "PUT", "POST", "DELETE", "OPTIONS", "HEAD", "PATCH", "TRACE",
+    ]
+    .iter()
+    .copied()
+    .filter(|method| !declared.contains(method))
+    .filter(|&method| !matches!(method, "OPTIONS" | "HEAD") || !declared.contains(&"GET"))
+    .map(|method| Probe {
+        path: path.to_string(),
+        method,
+        surface: Surface::Method,
+    })
+    .collect()
+}
+
+/// Probes prefixed and suffixed variants of the path with the method, except
+/// of the documented ones
+pub fn variant_probes(path: &str, method: &'static str, documented: &HashSet<&str>) -> Vec<Probe> {
+    if path.is_empty() {
+        return vec![];
+    }
+    PREFIXES
+        .iter()
+        .map(|prefix| format!("{prefix}{path}"))
+        .chain(SUFFIXES.iter().map(|suffix| format!("{path}{suffix}")))
+        .filter(|variant| !documented.contains(variant.as_str()))
+        .map(|path| Probe {
+            path,
+            method,
+            surface: Surface::Path,
+        })
+        .collect()
+}
+
+/// Probes the common hidden paths which are not documented with `GET`
+pub fn hidden_path_probes(documented: &HashSet<&str>) -> Vec<Probe> {
+    HIDDEN_PATHS
+        .iter()
+        .filter(|path| !documented.contains(*path))
+        .map(|path| Probe {
+            path: path.to_string(),
+            method: "GET",
+            surface: Surface::Path,
+        })
+        .collect()
+}
+
+pub fn is_rejection(status: u16) -> bool {
+    REJECTION_STATUS_CODES.contains(&status)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_method_probes() {
+        let methods = |declared| -> Vec<_> {
+            method_probes("items/{id}", declared)
+                .into_iter()
+                .map(|probe| probe.method)
+                .collect()
+        };
+
+        assert_eq!(
+            methods(&["GET", "DELETE"]),
+            ["PUT", "POST", "PATCH", "TRACE"]
+        );
+        assert_eq!(
+            methods(&["POST"]),
+            ["GET", "PUT", "DELETE", "OPTIONS", "HEAD", "PATCH", "TRACE"]
+        );
+        assert_eq!(
+            methods(&["GET", "PUT", "POST", "DELETE", "PATCH"]),
+            ["TRACE"]
+        );
+    }
+
+    #[test]
+    fn test_path_probes() {
+        let documented: HashSet<_> = ["users", "admin/users", "admin"].iter().copied().collect();
+
+        let variants = variant_probes("users", "POST", &documented);
+        assert_eq!(variants.len(), PREFIXES.len() + SUFFIXES.len() - 1);
+        assert!(variants.contains(&Probe {
+            path: "users.json".to_string(),
+            method: "POST",
+            surface: Surface::Path,
+        }));
+        assert!(!variants.iter().any(|probe| probe.path == "admin/users"));
+        assert!(variant_probes("", "GET", &documented).is_empty());
+
+        let hidden = hidden_path_probes(&documented);
+        assert_eq!(hidden.len(), HIDDEN_PATHS.len() - 1);
+        assert!(hidden.iter().all(|probe| probe.path != "admin"));
+    }
+}
@@ -0,0 +1,201 @@
+use std::str::FromStr;
+
+use openapiv3::{OpenAPI, Operation, PathItem, ReferenceOr};
+
+/// Selects the operations to fuzz
+#[derive(Debug)]
+pub struct OperationFilter {
+    include: Vec<Filter>,
+    exclude: Vec<Filter>,
+    skip_deprecated: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Filter {
+    /// Glob where `*` matches within a path segment and `**` across segments
+    Path(String),
+    Method(String),
+    Tag(String),
+    OperationId(String),
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("path", pattern)) => Ok(Filter::Path(pattern.to_string())),
+            Some(("method", method)) => Ok(Filter::Method(method.to_uppercase())),
+            Some(("tag", tag)) => Ok(Filter::Tag(tag.to_string())),
+            Some(("operation-id", operation_id)) => {
+                Ok(Filter::OperationId(operation_id.to_string()))
+            }
+            _ => Err(
+                "filter must start with one of path:, method:, tag: or operation-id:".to_string(),
+            ),
+        }
+    }
+}
+
+impl Filter {
+    fn matches(&self, path: &str, method: &str, operation: &Operation) -> bool {
+        match self {
+            Filter::Path(pattern) => glob_matches(pattern, path),
+            Filter::Method(expected) => expected == method,
+            Filter::Tag(tag) => operation.tags.contains(tag),
+            Filter::OperationId(operation_id) => {
+                operation.operation_id.as_ref() == Some(operation_id)
+            }
+        }
+    }
+}
+
+impl OperationFilter {
+    pub fn new(include: Vec<Filter>, exclude: Vec<Filter>, skip_deprecated: bool) -> Self {
+        OperationFilter {
+            include,
+            exclude,
+            skip_deprecated,
+        }
+    }
+
+    /// Operations are selected when they match any of the included filters,
+    /// if there are some, and none of the excluded ones
+    pub fn is_selected(&self, path: &str, method: &str, operation: &Operation) -> bool {
+        let matches = |filter: &Filter| filter.matches(path, method, operation);
+        !(self.skip_deprecated && operation.deprecated)
+            && (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+
+    /// Whether the path or the method of a request which does not belong to a
+    /// documented operation, e.g. a discovery probe, is excluded
+    pub fn excludes(&self, path: &str, method: &str) -> bool {
+        self.exclude.iter().any(|filter| match filter {
+            Filter::Path(pattern) => glob_matches(pattern, path),
+            Filter::Method(excluded) => excluded == method,
+            Filter::Tag(_) | Filter::OperationId(_) => false,
+        })
+    }
+
+    /// Removes the operations which are not selected from the specification,
+    /// together with the paths left without operations
+    pub fn apply(&self, openapi: &mut OpenAPI) {
+        openapi.paths.retain(|path, item| match item {
+            ReferenceOr::Item(item) => {
+                let mut operations = operations_mut(item);
+                for (method, operation) in operations.iter_mut() {
+                    if operation
+                        .as_ref()
+                        .is_some_and(|operation| !self.is_selected(path, method, operation))
+                    {
+                        **operation = None;
+                    }
+                }
+                operations.iter().any(|(_, operation)| operation.is_some())
+            }
+            ReferenceOr::Reference { .. } => true,
+        });
+    }
+}
+
+/// Returns the operation fields of the path item with their methods
+fn operations_mut(item: &mut PathItem) -> Vec<(&'static str, &mut Option<Operation>)> {
+    vec![
+        ("GET", &mut item.get),
+        ("PUT", &mut item.put),
+        ("POST", &mut item.post),
+        ("DELETE", &mut item.delete),
+        ("OPTIONS", &mut item.options),
+        ("HEAD", &mut item.head),
+        ("PATCH", &mut item.patch),
+        ("TRACE", &mut item.trace),
+    ]
+}
+
+/// Takes the declared operations out of the path item, with their methods
+pub fn take_operations(item: &mut PathItem) -> Vec<(&'static str, Operation)> {
+    operations_mut(item)
+        .into_iter()
+        .filter_map(|(method, operation)| operation.take().map(|operation| (method, operation)))
+        .collect()
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    if let Some(rest) = pattern.strip_prefix("**") {
+        return (0..=text.len())
+            .filter(|&i| text.is_char_boundary(i))
+            .any(|i| glob_matches(rest, &text[i..]));
+    }
+    if let Some(rest) = pattern.strip_prefix('*') {
+        let segment_end = text.find('/').unwrap_or(text.len());
+        return (0..=segment_end)
+            .filter(|&i| text.is_char_boundary(i))
+            .any(|i| glob_matches(rest, &text[i..]));
+    }
+    match (pattern.chars().next(), text.chars().next()) {
+        (None, None) => true,
+        (Some(p), Some(t)) if p == t || (p == '?' && t != '/') => {
+            glob_matches(&pattern[p.len_utf8()..], &text[t.len_utf8()..])
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        assert!(glob_matches("/users", "/users"));
+        assert!(glob_matches("/users/*", "/users/{id}"));
+        assert!(!glob_matches("/users/*", "/users/{id}/posts"));
+        assert!(glob_matches("/users/**", "/users/{id}/posts"));
+        assert!(glob_matches("/**/posts", "/users/{id}/posts"));
+        assert!(glob_matches("/v?/users", "/v1/users"));
+        assert!(!glob_matches("/users", "/users/{id}"));
+    }
+
+    #[test]
+    fn test_operation_filter() {
+        let operation = Operation {
+            tags: vec!["admin".to_string()],
+            operation_id: Some("deleteUser".to_string()),
+            deprecated: true,
+            ..Default::default()
+        };
+        let filter = |include: &[&str], exclude: &[&str], skip_deprecated| {
+            let parse = |filters: &[&str]| filters.iter().map(|f| f.parse().unwrap()).collect();
+            OperationFilter::new(parse(include), parse(exclude), skip_deprecated).is_selected(
+                "/users/{id}",
+                "DELETE",
+                &operation,
+            )
+        };
+
+        assert!(filter(&[], &[], false));
+        assert!(!filter(&[], &[], true));
+        assert!(filter(&["path:/users/*"], &[], false));
+        assert!(filter(&["tag:other", "method:delete"], &[], false));
+        assert!(!filter(&["tag:other"], &[], false));
+        assert!(!filter(&[], &["operation-id:deleteUser"], false));
+        assert!(!filter(&["path:/**"], &["tag:admin"], false));
+        assert!("users".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_excludes() {
+        let parse = |filters: &[&str]| filters.iter().map(|f| f.parse().unwrap()).collect();
+        let filter = OperationFilter::new(
+            parse(&["path:/users/**"]),
+            parse(&["method:delete", "path:/admin/**", "tag:admin"]),
+            true,
+        );
+
+        assert!(filter.excludes("/users/{id}", "DELETE"));
+        assert!(filter.excludes("/admin/users", "GET"));
+        assert!(!filter.excludes("/users/{id}", "PUT"));
+        assert!(!filter.excludes("/debug", "GET"));
+    }
+}
@@ -1,40 +1,114 @@
 use std::{
     borrow::Cow,
-    cell::RefCell,
-    collections::HashMap,
+    cell::{Cell, OnceCell, RefCell},
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
     fs::{self, File},
     mem,
     path::{Path, PathBuf},
     process::ExitCode,
     rc::Rc,
+    sync::Arc,
     thread,
     time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Context, Error, Result};
 use indexmap::IndexMap;
-use openapi_utils::ReferenceOrExt;
-use openapiv3::{OpenAPI, ReferenceOr, Response, StatusCode};
+use openapi_utils::{ParameterExt, ReferenceOrExt};
+use openapiv3::{OpenAPI, Operation, Parameter, Paths, ReferenceOr, Response, StatusCode};
 use proptest::{
-    prelude::any_with,
-    test_runner::{Config, FileFailurePersistence, TestCaseError, TestError, TestRunner},
+    prelude::{any_with, Strategy},
+    strategy::ValueTree,
+    test_runner::{Config, FileFailurePersistence, Reason, TestCaseError, TestError, TestRunner},
 };
+use rustls::ClientConfig;
 use serde::{Deserialize, Serialize};
 use ureq::{Agent, OrAnyStatus};
 use url::Url;
 
 use crate::{
-    arbitrary::{ArbitraryParameters, Payload},
+    arbitrary::{ArbitraryParameters, GenerationContext, Payload},
+    discovery::{self, Probe, Surface},
+    filter::{self, OperationFilter},
+    protocol::{self, NoResponse},
+    schema,
     stats::Stats,
 };
 
 const BACKOFF_STATUS_CODES: [u16; 2] = [429, 503];
 
+/// Stands for the status code in findings of requests left without response
+const TIMEOUT_STATUS: &str = "timeout";
+
+/// Limits shrinking of findings with protocol mutations, as every request
+/// which still hangs takes the whole timeout
+const PROTOCOL_SHRINK_TIME: Duration = Duration::from_secs(60);
+
+/// Limits how many payloads made of documented examples are sent before the
+/// generated ones
+const MAX_EXAMPLE_CASES: usize = 16;
+
+/// Returns the directory of the path, with the trailing slash
+fn parent(path: &str) -> &str {
+    path.rfind('/').map_or("", |slash| &path[..=slash])
+}
+
+/// What tells the route which responded apart from other routes
+#[derive(Debug)]
+struct ResponseSignature {
+    status: u16,
+    content_type: String,
+    /// Kind of JSON value of the body, if it is JSON
+    shape: &'static str,
+}
+
+impl ResponseSignature {
+    fn new(response: ureq::Response) -> Self {
+        let status = response.status();
+        let content_type = response.content_type().to_string();
+        let body = response.into_string();
+        let shape = match body.as_deref() {
+            Ok("") => "empty",
+            Ok(body) => match serde_json::from_str(body) {
+                Ok(serde_json::Value::Object(_)) => "object",
+                Ok(serde_json::Value::Array(_)) => "array",
+                Ok(_) => "scalar",
+                Err(_) => "not JSON",
+            },
+            Err(_) => "not JSON",
+        };
+        ResponseSignature {
+            status,
+            content_type,
+            shape,
+        }
+    }
+
+    /// Whether both responses could come from the same route. Status codes are
+    /// compared by their class only and empty bodies, like those of responses
+    /// to HEAD, match any shape.
+    fn matches(&self, other: &ResponseSignature) -> bool {
+        self.status / 100 == other.status / 100
+            && self.content_type.eq_ignore_ascii_case(&other.content_type)
+            && (self.shape == other.shape || self.shape == "empty" || other.shape == "empty")
+    }
+}
+
+impl Display for ResponseSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({})", self.status, self.content_type, self.shape)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct FuzzResult<'a> {
     pub payload: Payload,
     pub path: &'a str,
     pub method: &'a str,
+    /// Why the response is a finding, if not because of its status code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Default, Serialize)]
@@ -52,6 +126,10 @@ pub struct Fuzzer {
     results_dir: PathBuf,
     stats_dir: Option<PathBuf>,
     request_sender: RequestSender,
+    context: GenerationContext,
+    /// Paths of the specification before filtering and the filter, to probe
+    /// for undocumented methods and paths after fuzzing
+    discovery: Option<(Paths, OperationFilter)>,
 }
 
 impl Fuzzer {
@@ -62,6 +140,7 @@ impl Fuzzer {
         results_dir: PathBuf,
         stats_dir: Option<PathBuf>,
         request_sender: RequestSender,
+        context: GenerationContext,
     ) -> Fuzzer {
         Fuzzer {
             schema,
@@ -70,9 +149,18 @@ impl Fuzzer {
             results_dir,
             stats_dir,
             request_sender,
+            context,
+            discovery: None,
         }
     }
 
+    /// Probes for methods and paths which are neither documented in `paths`
+    /// nor excluded by the `filter`
+    pub fn with_discovery(mut self, paths: Paths, filter: OperationFilter) -> Self {
+        self.discovery = Some((paths, filter));
+        self
+    }
+
     pub fn run(&mut self) -> Result<ExitCode> {
         fs::create_dir_all(&self.results_dir).context(format!(
             "Unable to create directory: {:?}",
@@ -94,6 +182,10 @@ impl Fuzzer {
         let mut test_failed = false;
         let paths = mem::take(&mut self.schema.paths);
         let max_path_length = paths.iter().map(|(path, _)| path.len()).max().unwrap_or(0);
+        let probes = match self.discovery.take() {
+            Some((paths, filter)) => self.discovery_probes(paths, &filter)?,
+            None => vec![],
+        };
 
         println!("\x1B[1mMETHOD  {path:max_path_length$} STATUS   MEAN (μs) STD.DEV. MIN (μs)   MAX (μs)\x1B[0m",
             path = "PATH"
@@ -101,43 +193,109 @@ impl Fuzzer {
         for (path_with_params, mut ref_or_item) in paths {
             let path_with_params = path_with_params.trim_start_matches('/');
             let item = ref_or_item.to_item_mut();
-            let operations = vec![
-                ("GET", item.get.take()),
-                ("PUT", item.put.take()),
-                ("POST", item.post.take()),
-                ("DELETE", item.delete.take()),
-                ("OPTIONS", item.options.take()),
-                ("HEAD", item.head.take()),
-                ("PATCH", item.patch.take()),
-                ("TRACE", item.trace.take()),
-            ];
-
-            for (method, mut operation) in operations
-                .into_iter()
-                .filter_map(|(method, operation)| operation.map(|operation| (method, operation)))
-            {
+            let operations = filter::take_operations(item);
+
+            for (method, mut operation) in operations {
                 let responses = mem::take(&mut operation.responses.responses);
+                self.add_path_item_parameters(&mut operation, &item.parameters);
 
                 let stats = RefCell::new(FuzzStats::default());
+                let shrink_deadline = Cell::new(None);
 
-                let result = TestRunner::new(config.clone()).run(
-                    &any_with::<Payload>(Rc::new(ArbitraryParameters::new(operation))),
-                    |payload| {
-                        let now = Instant::now();
-                        let response = (self.request_sender)(path_with_params, method, &payload)
-                            .map_err(|e| {
-                                TestCaseError::Fail(format!("unable to send request: {e}").into())
-                            })?;
-
-                        let is_expected_response = self.is_expected_response(&response, &responses);
-                        stats.borrow_mut().times.push(now.elapsed().as_micros());
-                        stats.borrow_mut().did_failed.push(!is_expected_response);
-
-                        is_expected_response
-                            .then_some(())
-                            .ok_or(TestCaseError::Fail(response.status().to_string().into()))
-                    },
-                );
+                // Responses to path traversals are compared to the response to
+                // a conforming payload, sent when the first one is
+                let baseline_payloads = any_with::<Payload>(Rc::new(ArbitraryParameters::new(
+                    operation.clone(),
+                    self.context.conforming().examples_only(),
+                )));
+                let baseline = OnceCell::new();
+
+                let examples_context = self.context.examples_only();
+                let examples = any_with::<Payload>(Rc::new(ArbitraryParameters::new(
+                    operation.clone(),
+                    examples_context.clone(),
+                )));
+                let payloads = any_with::<Payload>(Rc::new(ArbitraryParameters::new(
+                    operation,
+                    self.context.clone(),
+                )));
+                let test = |payload: Payload| {
+                    // Shrinking is cut short by letting the remaining payloads
+                    // pass without sending them
+                    if shrink_deadline
+                        .get()
+                        .is_some_and(|deadline| Instant::now() > deadline)
+                    {
+                        return Ok(());
+                    }
+                    let now = Instant::now();
+                    let sent = (self.request_sender)(path_with_params, method, &payload);
+                    let elapsed = now.elapsed().as_micros();
+
+                    let response = match sent {
+                        Ok(response) => Ok(response),
+                        Err(error) => match error.downcast_ref::<NoResponse>() {
+                            Some(no_response) => Err(*no_response),
+                            None => {
+                                return Err(TestCaseError::Fail(
+                                    format!("unable to send request: {error}").into(),
+                                ))
+                            }
+                        },
+                    };
+                    let finding = match response {
+                        // Servers may close the connection instead of
+                        // responding to requests breaking the protocol
+                        Err(NoResponse::Closed) => None,
+                        Err(no_response) => Some(format!(
+                            "{TIMEOUT_STATUS}: {no_response}{}",
+                            payload
+                                .protocol_mutation()
+                                .map(|mutation| format!(", {mutation}"))
+                                .unwrap_or_default()
+                        )),
+                        Ok(response) => {
+                            let baseline = payload
+                                .is_path_traversal()
+                                .then(|| {
+                                    baseline.get_or_init(|| {
+                                        self.baseline(path_with_params, method, &baseline_payloads)
+                                    })
+                                })
+                                .and_then(Option::as_ref);
+                            self.finding(response, &payload, &responses, baseline)
+                        }
+                    };
+                    stats.borrow_mut().times.push(elapsed);
+                    stats.borrow_mut().did_failed.push(finding.is_some());
+                    if finding.is_some()
+                        && payload.protocol_mutation().is_some()
+                        && shrink_deadline.get().is_none()
+                    {
+                        shrink_deadline.set(Some(Instant::now() + PROTOCOL_SHRINK_TIME));
+                    }
+
+                    finding.map_or(Ok(()), |reason| Err(TestCaseError::Fail(reason.into())))
+                };
+
+                // Documented examples are sent first, as they are likely to pass
+                // validation and get deeper into the API
+                let mut runner = TestRunner::new(config.clone());
+                let mut result = Ok(());
+                match Fuzzer::example_trees(&mut runner, &examples, &examples_context) {
+                    Ok(trees) => {
+                        for tree in trees {
+                            if let Err(error) = runner.run_one(tree, test) {
+                                result = Err(error);
+                                break;
+                            }
+                        }
+                    }
+                    Err(reason) => result = Err(TestError::Abort(reason)),
+                }
+                if result.is_ok() {
+                    result = runner.run(&payloads, test);
+                }
                 let stats = stats.into_inner();
                 if let Some(dir) = &self.stats_dir {
                     Fuzzer::save_stats(dir, path_with_params, method, &stats)?;
@@ -154,6 +312,11 @@ impl Fuzzer {
             }
         }
 
+        let mut baselines = HashMap::new();
+        for (probe, payload) in probes {
+            test_failed |= self.discover(probe, payload, &mut baselines, max_path_length)?;
+        }
+
         if test_failed {
             Ok(ExitCode::FAILURE)
         } else {
@@ -161,6 +324,201 @@ impl Fuzzer {
         }
     }
 
+    /// Draws distinct payloads made of the documented examples, as many times
+    /// as there are examples of a single value but at most `MAX_EXAMPLE_CASES`
+    /// times. None are drawn if the operation documents no examples.
+    fn example_trees<S: Strategy<Value = Payload>>(
+        runner: &mut TestRunner,
+        examples: &S,
+        context: &GenerationContext,
+    ) -> Result<Vec<S::Tree>, Reason> {
+        let mut drawn = HashSet::new();
+        let mut trees = vec![];
+        let mut draws = 0;
+        loop {
+            let tree = examples.new_tree(runner)?;
+            // Examples are counted while drawing, as some strategies are
+            // built only then
+            draws += 1;
+            if draws > context.example_count().min(MAX_EXAMPLE_CASES) {
+                break;
+            }
+            // Payloads are compared by their serialization, as they hold JSON
+            // values which cannot be hashed
+            if drawn.insert(serde_json::to_string(&tree.current()).unwrap_or_default()) {
+                trees.push(tree);
+            }
+        }
+        Ok(trees)
+    }
+
+    /// Returns requests probing for undocumented methods and paths, each with
+    /// the documented examples of the path it is derived from. Methods and
+    /// paths excluded by the filter are not probed.
+    fn discovery_probes(
+        &self,
+        paths: Paths,
+        filter: &OperationFilter,
+    ) -> Result<Vec<(Probe, Payload)>> {
+        let documented: Vec<_> = paths
+            .keys()
+            .map(|path| path.trim_start_matches('/').to_string())
+            .collect();
+        let documented: HashSet<_> = documented.iter().map(String::as_str).collect();
+        let mut probes: Vec<_> = discovery::hidden_path_probes(&documented)
+            .into_iter()
+            .map(|probe| (probe, Payload::default()))
+            .collect();
+
+        for (path, mut ref_or_item) in paths {
+            let path = path.trim_start_matches('/');
+            let item = ref_or_item.to_item_mut();
+            let operations = filter::take_operations(item);
+            let declared: Vec<_> = operations.iter().map(|(method, _)| *method).collect();
+            let Some((method, mut operation)) = operations.into_iter().next() else {
+                continue;
+            };
+
+            self.add_path_item_parameters(&mut operation, &item.parameters);
+            let examples = any_with::<Payload>(Rc::new(ArbitraryParameters::new(
+                operation,
+                self.context.examples_only(),
+            )));
+            let payload = examples
+                .new_tree(&mut TestRunner::deterministic())
+                .map_err(Error::msg)?
+                .current();
+
+            probes.extend(
+                discovery::method_probes(path, &declared)
+                    .into_iter()
+                    .chain(discovery::variant_probes(path, method, &documented))
+                    .map(|probe| (probe, payload.clone())),
+            );
+        }
+        probes.retain(|(probe, _)| !filter.excludes(&format!("/{}", probe.path), probe.method));
+        Ok(probes)
+    }
+
+    /// Sends the probe and saves the response as a finding, unless the server
+    /// refuses the request or responds like to a nonexistent path or method,
+    /// e.g. by a catch-all route or by requiring authentication. The responses
+    /// to nonexistent ones are kept in `baselines`. Returns whether the
+    /// response is a finding.
+    fn discover(
+        &self,
+        probe: Probe,
+        payload: Payload,
+        baselines: &mut HashMap<(Surface, String), Option<ResponseSignature>>,
+        max_path_length: usize,
+    ) -> Result<bool> {
+        // Paths are compared within the same directory and with the same
+        // method, methods for the same path
+        let key = match probe.surface {
+            Surface::Path => (
+                probe.surface,
+                format!("{} {}", probe.method, parent(&probe.path)),
+            ),
+            Surface::Method => (probe.surface, probe.path.clone()),
+        };
+        let baseline = baselines
+            .entry(key)
+            .or_insert_with(|| self.probe_baseline(&probe, &payload));
+        let Probe {
+            path,
+            method,
+            surface,
+        } = probe;
+
+        let (status, reason) = match (self.request_sender)(&path, method, &payload) {
+            Ok(response) => {
+                let status = response.status();
+                if discovery::is_rejection(status) || self.ignored_status_codes.contains(&status) {
+                    return Ok(false);
+                }
+                let signature = ResponseSignature::new(response);
+                if baseline
+                    .as_ref()
+                    .is_some_and(|baseline| baseline.matches(&signature))
+                {
+                    return Ok(false);
+                }
+                (status.to_string(), surface.to_string())
+            }
+            Err(error) => match error.downcast_ref::<NoResponse>() {
+                Some(NoResponse::Closed) => return Ok(false),
+                Some(no_response) => (
+                    TIMEOUT_STATUS.to_string(),
+                    format!("{surface}, {no_response}"),
+                ),
+                // The other probes are still sent
+                None => {
+                    println!(
+                        "{method:7} {path:max_path_length$} {:^7} {error:#}",
+                        "error"
+                    );
+                    return Ok(false);
+                }
+            },
+        };
+
+        self.save_finding(&path, method, payload, &status, Some(reason.clone()))?;
+        println!("{method:7} {path:max_path_length$} {status:^7} {reason}");
+        Ok(true)
+    }
+
+    /// Sends the payload of the probe to a random path next to the probed one
+    /// or with a random method, neither of which exists in the API
+    fn probe_baseline(&self, probe: &Probe, payload: &Payload) -> Option<ResponseSignature> {
+        let mut runner = TestRunner::default();
+        let mut random = |pattern: &str| {
+            pattern
+                .new_tree(&mut runner)
+                .ok()
+                .map(|tree| tree.current())
+        };
+        let response = match probe.surface {
+            Surface::Path => {
+                let path = format!(
+                    "{}openapi-fuzzer-{}",
+                    parent(&probe.path),
+                    random("[a-z0-9]{16}")?
+                );
+                (self.request_sender)(&path, probe.method, payload)
+            }
+            Surface::Method => (self.request_sender)(&probe.path, &random("[A-Z]{12}")?, payload),
+        };
+        Some(ResponseSignature::new(response.ok()?))
+    }
+
+    /// Adds parameters shared by all operations of a path, unless the operation
+    /// overrides them by a parameter with the same name and location
+    fn add_path_item_parameters(
+        &self,
+        operation: &mut Operation,
+        parameters: &[ReferenceOr<Parameter>],
+    ) {
+        let resolver = self.context.resolver();
+        let is_same = |a: &Parameter, b: &Parameter| {
+            mem::discriminant(a) == mem::discriminant(b)
+                && a.parameter_data().name == b.parameter_data().name
+        };
+
+        for parameter in parameters {
+            let Some(resolved) = resolver.parameter(parameter) else {
+                continue;
+            };
+            let is_overridden = operation
+                .parameters
+                .iter()
+                .filter_map(|parameter| resolver.parameter(parameter))
+                .any(|other| is_same(resolved, other));
+            if !is_overridden {
+                operation.parameters.push(parameter.clone());
+            }
+        }
+    }
+
     pub fn send_request_with_backoff(
         url: &Url,
         path_with_params: &str,
@@ -168,12 +526,20 @@ impl Fuzzer {
         payload: &Payload,
         extra_headers: &HashMap<String, String>,
         agent: &Agent,
+        tls_config: &Arc<ClientConfig>,
     ) -> Result<ureq::Response> {
         let max_backoff = 10;
 
         for backoff in 0..max_backoff {
-            let response =
-                Fuzzer::send_request(url, path_with_params, method, payload, extra_headers, agent)?;
+            let response = Fuzzer::send_request(
+                url,
+                path_with_params,
+                method,
+                payload,
+                extra_headers,
+                agent,
+                tls_config,
+            )?;
             if !BACKOFF_STATUS_CODES.contains(&response.status()) {
                 return Ok(response);
             }
@@ -195,16 +561,27 @@ impl Fuzzer {
         payload: &Payload,
         extra_headers: &HashMap<String, String>,
         agent: &Agent,
+        tls_config: &Arc<ClientConfig>,
     ) -> Result<ureq::Response> {
+        // Path parameters are already serialized according to their style
         let mut path_with_params = path_with_params.to_owned();
         for (name, value) in payload.path_params().iter() {
             path_with_params = path_with_params.replace(&format!("{{{name}}}"), value);
         }
-        let mut request = agent.request_url(method, &url.join(&path_with_params)?);
+        let unnormalized_path = format!("{}{path_with_params}", url.path());
+        let mut url = url.join(&path_with_params)?;
 
-        for (param, value) in payload.query_params().iter() {
-            request = request.query(param, value);
+        // Query parameters are already serialized according to their style
+        let query = payload
+            .query_params()
+            .iter()
+            .map(|(param, value)| format!("{param}={value}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        if !query.is_empty() {
+            url.set_query(Some(&query));
         }
+        let mut request = agent.request_url(method, &url);
 
         // Add headers overriding genereted ones with extra headers from command line
         for (header, value) in payload.headers().iter() {
@@ -212,6 +589,30 @@ impl Fuzzer {
             request = request.set(header, value);
         }
 
+        // Add cookies overriding generated ones with cookies from command line
+        let extra_cookies = extra_headers.get("cookie").map_or("", String::as_str);
+        let extra_cookie_names: Vec<_> = extra_cookies
+            .split(';')
+            .filter_map(|cookie| cookie.split('=').next())
+            .map(str::trim)
+            .collect();
+        let cookies: Vec<_> = payload
+            .cookies()
+            .iter()
+            .filter(|(name, _)| !extra_cookie_names.contains(&name.as_str()))
+            .map(|(name, value)| format!("{name}={value}"))
+            .chain(
+                extra_cookies
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|cookie| !cookie.is_empty())
+                    .map(String::from),
+            )
+            .collect();
+        if !cookies.is_empty() {
+            request = request.set("Cookie", &cookies.join("; "));
+        }
+
         // Add remaining extra headers
         for (header, value) in extra_headers.iter() {
             if request.header(header).is_none() {
@@ -219,14 +620,107 @@ impl Fuzzer {
             }
         }
 
-        match payload.body() {
-            Some(json) => request.send_json(json.clone()),
+        let body = match payload.body() {
+            Some(body) => {
+                if request.header("Content-Type").is_none() {
+                    request = request.set("Content-Type", &body.content_type);
+                }
+                Some(body.to_bytes())
+            }
+            None => None,
+        };
+
+        // The URL normalizes dot segments and cuts the path at '#', so path
+        // traversals are written to the connection by hand like mutations
+        if payload.is_path_traversal() || payload.protocol_mutation().is_some() {
+            let path = if payload.is_path_traversal() {
+                unnormalized_path
+            } else {
+                url.path().to_string()
+            };
+            let target = match url.query() {
+                Some(query) => format!("{path}?{query}"),
+                None => path,
+            };
+            return protocol::send(
+                &request,
+                &target,
+                body.as_deref(),
+                payload.protocol_mutation(),
+                tls_config,
+            );
+        }
+        match body {
+            Some(body) => request.send_bytes(&body),
             None => request.call(),
         }
         .or_any_status()
         .map_err(Into::into)
     }
 
+    /// Returns the signature of the response to a payload conforming to the
+    /// specification, none if it cannot be sent
+    fn baseline(
+        &self,
+        path_with_params: &str,
+        method: &str,
+        payloads: &impl Strategy<Value = Payload>,
+    ) -> Option<ResponseSignature> {
+        let payload = payloads
+            .new_tree(&mut TestRunner::deterministic())
+            .ok()?
+            .current();
+        let response = (self.request_sender)(path_with_params, method, &payload).ok()?;
+        Some(ResponseSignature::new(response))
+    }
+
+    /// Returns why the response to the payload is a finding, if it is one. The
+    /// responses to path traversals are compared to the `baseline`.
+    fn finding(
+        &self,
+        response: ureq::Response,
+        payload: &Payload,
+        responses: &IndexMap<StatusCode, ReferenceOr<Response>>,
+        baseline: Option<&ResponseSignature>,
+    ) -> Option<String> {
+        let status = response.status();
+        if let Some(mutation) = payload.protocol_mutation() {
+            // Requests breaking the protocol may be rejected with any client error
+            (500..=599)
+                .contains(&status)
+                .then(|| format!("{status}: server error on {mutation}"))
+        } else if let Some(violation) = payload.violation() {
+            // Invalid input should be rejected with a client error
+            match status {
+                200..=299 => Some(format!("{status}: invalid input accepted, {violation}")),
+                500..=599 => Some(format!("{status}: invalid input crashed, {violation}")),
+                _ => None,
+            }
+        } else if let Some(mutation) = payload.mutation() {
+            // Malformed bodies may be rejected with any client error
+            (500..=599)
+                .contains(&status)
+                .then(|| format!("{status}: malformed body crashed, {mutation}"))
+        } else if !self.is_expected_response(&response, responses) {
+            Some(status.to_string())
+        } else if let Some(baseline) = baseline {
+            // Path traversals reaching another route succeed with a response
+            // the fuzzed route does not give
+            let signature = ResponseSignature::new(response);
+            ((200..=299).contains(&status) && !signature.matches(baseline)).then(|| {
+                format!("{status}: response to path traversal is {signature} instead of {baseline}")
+            })
+        } else {
+            let leaked = self.leaked_write_only_properties(response, responses);
+            (!leaked.is_empty()).then(|| {
+                format!(
+                    "{status}: write-only properties in response: {}",
+                    leaked.join(", ")
+                )
+            })
+        }
+    }
+
     fn is_expected_response(
         &self,
         resp: &ureq::Response,
@@ -238,12 +732,42 @@ impl Fuzzer {
                 && resp.status() / 100 != 5)
     }
 
+    /// Returns write-only properties, e.g. passwords, which the response
+    /// contains even though they should never be sent by the server
+    fn leaked_write_only_properties(
+        &self,
+        response: ureq::Response,
+        responses: &IndexMap<StatusCode, ReferenceOr<Response>>,
+    ) -> Vec<String> {
+        let resolver = self.context.resolver();
+        let status = response.status();
+        let schema = responses
+            .get(&StatusCode::Code(status))
+            .or_else(|| responses.get(&StatusCode::Range(status / 100)))
+            .and_then(|response| resolver.response(response))
+            .and_then(|response| {
+                response
+                    .content
+                    .iter()
+                    .find(|(media_type_name, _)| media_type_name.contains("json"))
+            })
+            .and_then(|(_, media_type)| media_type.schema.as_ref());
+
+        match (schema, response.into_json::<serde_json::Value>()) {
+            (Some(schema), Ok(json)) => {
+                schema::write_only_properties(resolver, resolver.schema(schema), &json)
+            }
+            _ => vec![],
+        }
+    }
+
     fn save_finding(
         &self,
         path: &str,
         method: &str,
         payload: Payload,
-        status_code: u16,
+        status_code: &str,
+        reason: Option<String>,
     ) -> Result<()> {
         let file = format!(
             "{}-{method}-{status_code}.json",
@@ -256,6 +780,7 @@ impl Fuzzer {
                 payload,
                 path,
                 method,
+                reason,
             },
         )
         .map_err(Into::into)
@@ -282,11 +807,15 @@ impl Fuzzer {
         let status = match result {
             Err(TestError::Fail(reason, payload)) => {
                 let reason: Cow<str> = reason.message().into();
-                let status_code = reason
-                    .parse::<u16>()
-                    .map_err(|_| Error::msg(reason.into_owned()))?;
+                let (status_code, detail) = match reason.split_once(": ") {
+                    Some((status_code, detail)) => (status_code, Some(detail.to_string())),
+                    None => (reason.as_ref(), None),
+                };
+                if status_code != TIMEOUT_STATUS && status_code.parse::<u16>().is_err() {
+                    return Err(Error::msg(reason.to_string()));
+                }
 
-                self.save_finding(path_with_params, method, payload, status_code)?;
+                self.save_finding(path_with_params, method, payload, status_code, detail)?;
                 "failed"
             }
             Ok(()) => "ok",
@@ -303,3 +832,239 @@ impl Fuzzer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    use crate::verifier;
+
+    #[test]
+    fn test_resend_timeout() {
+        let result: FuzzResult = serde_json::from_str(
+            r#"{
+                "payload": {
+                    "parameters": {"headers": [], "path": [["id", "1"]], "query": []},
+                    "body": null,
+                    "protocol_mutation": {
+                        "kind": "framing",
+                        "content_length": ["100"],
+                        "transfer_encoding": null
+                    }
+                },
+                "path": "items/{id}",
+                "method": "POST",
+                "reason": "timeout: no response in 10s, Content-Length [\"100\"]"
+            }"#,
+        )
+        .unwrap();
+
+        // The server never responds, waiting for the rest of the body
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let tls_config = Arc::new(verifier::skip_tls_verification_config());
+        let error = Fuzzer::send_request(
+            &url,
+            result.path,
+            result.method,
+            &result.payload,
+            &HashMap::new(),
+            &ureq::agent(),
+            &tls_config,
+        )
+        .unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&NoResponse::Timeout));
+    }
+
+    #[test]
+    fn test_path_traversal_target() {
+        let values = [
+            "../../../../../../etc/passwd",
+            "..\\",
+            "%2e%2e%2f",
+            ".",
+            "#",
+        ];
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/api/", listener.local_addr().unwrap())).unwrap();
+        let server = thread::spawn(move || {
+            let mut request_lines = vec![];
+            for _ in 0..values.len() {
+                let mut stream = listener.accept().unwrap().0;
+                let mut request_line = String::new();
+                BufReader::new(&stream)
+                    .read_line(&mut request_line)
+                    .unwrap();
+                request_lines.push(request_line);
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n[]")
+                    .unwrap();
+            }
+            request_lines
+        });
+
+        let tls_config = Arc::new(verifier::skip_tls_verification_config());
+        for value in values {
+            let payload: Payload = serde_json::from_value(serde_json::json!({
+                "parameters": {"headers": [], "path": [["id", value]], "query": [["q", "1"]]},
+                "body": null,
+            }))
+            .unwrap();
+            assert!(payload.is_path_traversal());
+            let response = Fuzzer::send_request(
+                &url,
+                "items/{id}/details",
+                "GET",
+                &payload,
+                &HashMap::new(),
+                &ureq::agent(),
+                &tls_config,
+            )
+            .unwrap();
+            assert_eq!(response.content_type(), "application/json");
+        }
+
+        // The path is sent without normalizing it
+        assert_eq!(
+            server.join().unwrap(),
+            [
+                "GET /api/items/../../../../../../etc/passwd/details?q=1 HTTP/1.1\r\n",
+                "GET /api/items/..\\/details?q=1 HTTP/1.1\r\n",
+                "GET /api/items/%2e%2e%2f/details?q=1 HTTP/1.1\r\n",
+                "GET /api/items/./details?q=1 HTTP/1.1\r\n",
+                "GET /api/items/#/details?q=1 HTTP/1.1\r\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_response_signature() {
+        let signature = |response: &str| ResponseSignature::new(response.parse().unwrap());
+        let baseline = signature("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{}");
+
+        let same_route =
+            signature("HTTP/1.1 201 Created\r\nContent-Type: application/json\r\n\r\n{\"id\": 1}");
+        assert!(same_route.matches(&baseline));
+        let list = signature("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n[]");
+        assert!(!list.matches(&baseline));
+        let file = signature("HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\r\nroot:x:0:0");
+        assert!(!file.matches(&baseline));
+        assert_eq!(file.to_string(), "200 text/plain (not JSON)");
+        let not_found =
+            signature("HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\n\r\n{}");
+        assert!(!not_found.matches(&baseline));
+    }
+
+    #[test]
+    fn test_discover() {
+        let results_dir = std::env::temp_dir().join("openapi-fuzzer-test-discover");
+        fs::create_dir_all(&results_dir).unwrap();
+        let probe = |path: &str, method, surface| Probe {
+            path: path.to_string(),
+            method,
+            surface,
+        };
+        let discover = |respond: fn(&str, &str) -> Result<&'static str>, probes: &[Probe]| {
+            let fuzzer = Fuzzer::new(
+                OpenAPI::default(),
+                vec![],
+                1,
+                results_dir.clone(),
+                None,
+                Box::new(move |path, method, _| Ok(respond(path, method)?.parse()?)),
+                GenerationContext::default(),
+            );
+            let mut baselines = HashMap::new();
+            probes
+                .iter()
+                .map(|probe| {
+                    fuzzer
+                        .discover(probe.clone(), Payload::default(), &mut baselines, 0)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        let probes = [
+            probe("admin", "GET", Surface::Path),
+            probe("items", "DELETE", Surface::Method),
+        ];
+
+        // Only the probed path and method exist
+        let found = discover(
+            |path, method| {
+                Ok(match (path, method) {
+                    ("admin", "GET") | ("items", "DELETE") => {
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<html>"
+                    }
+                    ("items", "GET") => "HTTP/1.1 200 OK\r\n\r\n",
+                    ("items", _) => "HTTP/1.1 405 Method Not Allowed\r\n\r\n",
+                    _ => "HTTP/1.1 404 Not Found\r\n\r\n",
+                })
+            },
+            &probes,
+        );
+        assert_eq!(found, [true, true]);
+
+        // Every path and method is answered by a catch-all route
+        let found = discover(
+            |_, _| Ok("HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<html>"),
+            &probes,
+        );
+        assert_eq!(found, [false, false]);
+
+        // Every request requires authentication
+        let found = discover(
+            |_, _| Ok("HTTP/1.1 401 Unauthorized\r\nContent-Type: application/json\r\n\r\n{}"),
+            &probes,
+        );
+        assert_eq!(found, [false, false]);
+
+        // Probes failing to send do not stop the following ones
+        let found = discover(
+            |path, _| match path {
+                "admin" => Err(anyhow!("connection refused")),
+                "items" => Ok("HTTP/1.1 200 OK\r\n\r\n[]"),
+                _ => Ok("HTTP/1.1 404 Not Found\r\n\r\n"),
+            },
+            &[probes[0].clone(), probe("items", "GET", Surface::Path)],
+        );
+        assert_eq!(found, [false, true]);
+
+        fs::remove_dir_all(results_dir).unwrap();
+    }
+
+    #[test]
+    fn test_result_round_trip() {
+        // Numbers are saved and resent with their exact digits, also the ones
+        // which do not fit into 64 bits
+        let body = concat!(
+            r#"{"big":170141183460469231731687303715884105727,"exponent":1e300,"#,
+            r#""float":0.1,"negative":-9223372036854775809,"whole":1.0}"#
+        );
+        let result = FuzzResult {
+            payload: serde_json::from_value(serde_json::json!({
+                "parameters": {"headers": [], "path": [], "query": []},
+                "body": {
+                    "content_type": "application/json",
+                    "content": {"json": serde_json::from_str::<serde_json::Value>(body).unwrap()},
+                },
+            }))
+            .unwrap(),
+            path: "items",
+            method: "POST",
+            reason: None,
+        };
+
+        let saved = serde_json::to_string_pretty(&result).unwrap();
+        let resent: FuzzResult = serde_json::from_str(&saved).unwrap();
+        assert_eq!(
+            String::from_utf8(resent.payload.body().unwrap().to_bytes()).unwrap(),
+            body
+        );
+        assert_eq!(serde_json::to_string_pretty(&resent).unwrap(), saved);
+    }
+}
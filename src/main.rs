@@ -1,22 +1,38 @@
 mod arbitrary;
+mod dictionary;
+mod discovery;
+mod filter;
 mod fuzzer;
+mod mutation;
+mod protocol;
+mod resolver;
+mod schema;
 mod stats;
 mod verifier;
+mod xml;
 
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::ExitCode;
+use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::{fs, time::Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use argh::FromArgs;
 use fuzzer::Fuzzer;
-use openapi_utils::SpecExt;
 use openapiv3::OpenAPI;
 use url::{ParseError, Url};
 
-use crate::fuzzer::FuzzResult;
+use crate::{
+    arbitrary::{GenerationContext, GenerationOptions, SampleFile},
+    dictionary::Dictionary,
+    filter::{Filter, OperationFilter},
+    fuzzer::FuzzResult,
+    resolver::Resolver,
+    xml::XmlHints,
+};
 
 #[derive(FromArgs, PartialEq, Debug)]
 /// openapi-fuzzer - black-box fuzzer that fuzzes APIs based on OpenAPI Specification
@@ -73,6 +89,88 @@ struct RunArgs {
     /// do not use rate limiting
     #[argh(switch)]
     no_rate_limiting: bool,
+
+    /// sometimes send polymorphic objects with a discriminator not matching
+    /// their schema
+    #[argh(switch)]
+    mismatched_discriminators: bool,
+
+    /// sometimes send request bodies with a content type not matching their
+    /// media type, to check how the content negotiation handles them
+    #[argh(switch)]
+    mismatched_content_types: bool,
+
+    /// sometimes omit required request bodies, to check that the api rejects
+    /// such requests
+    #[argh(switch)]
+    omit_required_bodies: bool,
+
+    /// sometimes send request bodies malformed on the byte level, e.g.
+    /// truncated json or invalid utf-8, and report server errors caused by
+    /// them
+    #[argh(switch)]
+    malformed_bodies: bool,
+
+    /// sometimes send requests breaking the http protocol, e.g. with
+    /// conflicting content-length and transfer-encoding headers, and report
+    /// server errors or requests left without response
+    #[argh(switch)]
+    protocol_mutations: bool,
+
+    /// break one constraint of the specification in each payload and report
+    /// responses accepting the payload (2xx) or failing on it (5xx). the rest
+    /// of the payload conforms to the specification, so the dictionary is not
+    /// used and the other switches altering payloads cannot be combined with it
+    #[argh(switch)]
+    negative: bool,
+
+    /// send read-only properties in request bodies to probe for mass
+    /// assignment
+    #[argh(switch)]
+    send_read_only: bool,
+
+    /// how many times a recursive schema may be nested in itself (default: 4)
+    #[argh(option, default = "4")]
+    max_recursion_depth: u32,
+
+    /// send unencoded path traversal payloads in path parameters and report
+    /// responses which seem to come from another endpoint
+    #[argh(switch)]
+    path_traversal: bool,
+
+    /// file uploaded in binary parts of multipart bodies besides random data,
+    /// may be repeated
+    #[argh(option)]
+    sample_file: Vec<PathBuf>,
+
+    /// do not mix the built-in attack payloads, e.g. sql injections or format
+    /// strings, into generated strings
+    #[argh(switch)]
+    no_dictionary: bool,
+
+    /// file with one string per line mixed into generated strings, may be
+    /// repeated
+    #[argh(option)]
+    wordlist: Vec<PathBuf>,
+
+    /// fuzz only operations matching the filter, may be repeated. filters are
+    /// path:<glob>, method:<method>, tag:<tag> or operation-id:<id>
+    #[argh(option)]
+    include: Vec<Filter>,
+
+    /// skip operations matching the filter, may be repeated
+    #[argh(option)]
+    exclude: Vec<Filter>,
+
+    /// skip operations marked as deprecated
+    #[argh(switch)]
+    skip_deprecated: bool,
+
+    /// after fuzzing, send undeclared methods to each path and probe common
+    /// hidden paths and variants of the documented ones, reporting responses
+    /// other than 404, 405 and 501
+    #[argh(switch)]
+    discover: bool,
 }
 
 #[derive(FromArgs, Debug, PartialEq)]
@@ -145,28 +243,96 @@ fn main() -> Result<ExitCode> {
 
     let exit_code = match args.subcommands {
         Subcommands::Run(args) => {
+            // Everything but the broken constraint of negative payloads
+            // conforms to the specification
+            let altering = [
+                (
+                    "--mismatched-discriminators",
+                    args.mismatched_discriminators,
+                ),
+                ("--mismatched-content-types", args.mismatched_content_types),
+                ("--omit-required-bodies", args.omit_required_bodies),
+                ("--malformed-bodies", args.malformed_bodies),
+                ("--protocol-mutations", args.protocol_mutations),
+                ("--send-read-only", args.send_read_only),
+                ("--path-traversal", args.path_traversal),
+            ];
+            let altering: Vec<_> = altering
+                .iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(flag, _)| *flag)
+                .collect();
+            if args.negative && !altering.is_empty() {
+                bail!("--negative cannot be combined with {}", altering.join(", "));
+            }
+
             let specfile = std::fs::read_to_string(&args.spec)
                 .context(format!("Unable to read {:?}", &args.spec))?;
-            let openapi_schema: OpenAPI =
+            let mut openapi_schema: OpenAPI =
                 serde_yaml::from_str(&specfile).context("Failed to parse schema")?;
-            let openapi_schema = openapi_schema.deref_all();
+            let filter = OperationFilter::new(args.include, args.exclude, args.skip_deprecated);
+            // Filtered out operations are still documented, so that the
+            // discovery does not probe for them
+            let documented = args.discover.then(|| openapi_schema.paths.clone());
+            filter.apply(&mut openapi_schema);
 
             let request_sender = create_sender(
-                create_agent(!args.skip_tls_verify),
+                create_tls_config(!args.skip_tls_verify),
                 args.url.into(),
                 args.header.into_iter().map(Into::into).collect(),
                 args.no_rate_limiting,
             );
+            let sample_files = args
+                .sample_file
+                .iter()
+                .map(|path| {
+                    Ok(SampleFile {
+                        name: path
+                            .file_name()
+                            .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+                        data: fs::read(path).context(format!("Unable to read {path:?}"))?,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            let mut dictionary = if args.no_dictionary {
+                Dictionary::default()
+            } else {
+                Dictionary::builtin()
+            };
+            for path in &args.wordlist {
+                let wordlist =
+                    fs::read_to_string(path).context(format!("Unable to read {path:?}"))?;
+                dictionary.add_wordlist(&path.to_string_lossy(), &wordlist);
+            }
+            let resolver = Resolver::new(openapi_schema.components.take())
+                .with_xml_hints(XmlHints::from_specification(&specfile));
+            let options = GenerationOptions {
+                mismatched_discriminators: args.mismatched_discriminators,
+                mismatched_content_types: args.mismatched_content_types,
+                omit_required_bodies: args.omit_required_bodies,
+                malformed_bodies: args.malformed_bodies,
+                protocol_mutations: args.protocol_mutations,
+                negative: args.negative,
+                read_only_properties: args.send_read_only,
+                max_recursion_depth: args.max_recursion_depth,
+                path_traversal: args.path_traversal,
+                sample_files,
+                dictionary,
+            };
             let now = Instant::now();
-            let exit_code = Fuzzer::new(
+            let mut fuzzer = Fuzzer::new(
                 openapi_schema,
                 args.ignore_status_code,
                 args.max_test_case_count,
                 args.results_dir,
                 args.stats_dir,
                 request_sender,
-            )
-            .run()?;
+                GenerationContext::new(Rc::new(resolver), Rc::new(options)),
+            );
+            if let Some(documented) = documented {
+                fuzzer = fuzzer.with_discovery(documented, filter);
+            }
+            let exit_code = fuzzer.run()?;
             println!("Elapsed time: {}s", now.elapsed().as_secs());
             exit_code
         }
@@ -175,7 +341,7 @@ fn main() -> Result<ExitCode> {
                 .context(format!("Unable to read {:?}", &args.file))?;
             let result: FuzzResult = serde_json::from_str(&json)?;
 
-            let agent = create_agent(!args.skip_tls_verify);
+            let tls_config = create_tls_config(!args.skip_tls_verify);
 
             let response = Fuzzer::send_request(
                 &args.url.into(),
@@ -183,7 +349,8 @@ fn main() -> Result<ExitCode> {
                 result.method,
                 &result.payload,
                 &args.header.into_iter().map(Into::into).collect(),
-                &agent,
+                &create_agent(tls_config.clone()),
+                &tls_config,
             )?;
             eprintln!("{} ({})", response.status(), response.status_text());
             println!("{}", response.into_string()?);
@@ -194,23 +361,25 @@ fn main() -> Result<ExitCode> {
     Ok(exit_code)
 }
 
-fn create_agent(verify_cert: bool) -> ureq::Agent {
+fn create_tls_config(verify_cert: bool) -> Arc<rustls::ClientConfig> {
     if verify_cert {
-        ureq::agent()
+        Arc::new(verifier::native_certs_config())
     } else {
-        let conf = verifier::skip_tls_verification_config();
-        ureq::AgentBuilder::new()
-            .tls_config(std::sync::Arc::new(conf))
-            .build()
+        Arc::new(verifier::skip_tls_verification_config())
     }
 }
 
+fn create_agent(tls_config: Arc<rustls::ClientConfig>) -> ureq::Agent {
+    ureq::AgentBuilder::new().tls_config(tls_config).build()
+}
+
 fn create_sender(
-    agent: ureq::Agent,
+    tls_config: Arc<rustls::ClientConfig>,
     url: Url,
     extra_headers: HashMap<String, String>,
     no_rate_limiting: bool,
 ) -> fuzzer::RequestSender {
+    let agent = create_agent(tls_config.clone());
     if no_rate_limiting {
         Box::new(move |path_with_params, method, payload| {
             Fuzzer::send_request(
@@ -220,6 +389,7 @@ fn create_sender(
                 payload,
                 &extra_headers,
                 &agent,
+                &tls_config,
             )
         })
     } else {
@@ -231,6 +401,7 @@ fn create_sender(
                 payload,
                 &extra_headers,
                 &agent,
+                &tls_config,
             )
         })
     }
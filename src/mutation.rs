@@ -0,0 +1,223 @@
+use std::fmt::{self, Display};
+
+use proptest::{
+    sample::select,
+    strategy::{BoxedStrategy, Just, Strategy, Union},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::arbitrary::{Body, Content};
+
+/// Malformation of a request body, which is then sent as raw bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mutation {
+    Truncated,
+    DuplicateKey,
+    DeepNesting,
+    HugeNumber,
+    InvalidUtf8,
+    ByteOrderMark,
+    MismatchedContent,
+}
+
+impl Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Mutation::Truncated => "truncated body",
+            Mutation::DuplicateKey => "duplicate key",
+            Mutation::DeepNesting => "deeply nested arrays",
+            Mutation::HugeNumber => "huge number",
+            Mutation::InvalidUtf8 => "invalid UTF-8",
+            Mutation::ByteOrderMark => "byte order mark",
+            Mutation::MismatchedContent => "body not matching the content type",
+        };
+        f.write_str(description)
+    }
+}
+
+/// Nesting depths around the limits of common JSON parsers
+const NESTING_DEPTHS: [usize; 4] = [513, 1_001, 10_001, 100_000];
+
+/// Byte sequences which are not valid UTF-8: a stray continuation byte,
+/// overlong encodings, a surrogate, a code point above U+10FFFF and an
+/// unfinished sequence
+const INVALID_UTF8: &[&[u8]] = &[
+    b"\x80",
+    b"\xff",
+    b"\xc0\xaf",
+    b"\xe0\x80\xaf",
+    b"\xed\xa0\x80",
+    b"\xf4\x90\x80\x80",
+    b"\xe2\x82",
+];
+
+/// Documents of other formats, with a part of the content type they are not
+/// sent under
+const FOREIGN_BODIES: &[(&str, &[u8])] = &[
+    ("json", br#"{"a":[1,{"b":null}]}"#),
+    ("xml", br#"<?xml version="1.0"?><a><b/></a>"#),
+    ("x-www-form-urlencoded", b"a=1&b=%ZZ&=&&c"),
+    (
+        "form-data",
+        b"--x\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1\r\n--x--\r\n",
+    ),
+    ("text/", b"plain text"),
+    ("image/", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+];
+
+fn huge_numbers() -> Vec<String> {
+    vec![
+        format!("1{}", "0".repeat(10_000)),
+        format!("-0.{}1", "0".repeat(10_000)),
+        "1e999999999".to_string(),
+        "-1e-999999999".to_string(),
+        "123456789".repeat(1_000),
+    ]
+}
+
+/// Serializes the JSON with the raw bytes in place of the value of its first
+/// property, or in place of the whole document if it is not an object
+fn with_first_value(json: &serde_json::Value, raw: &[u8]) -> Vec<u8> {
+    let Some((key, properties)) = json
+        .as_object()
+        .and_then(|properties| Some((properties.keys().next()?, properties)))
+    else {
+        return raw.to_vec();
+    };
+    let mut rest = properties.clone();
+    rest.remove(key);
+    let rest = serde_json::Value::Object(rest).to_string();
+    let rest = &rest[1..];
+    let separator = if rest == "}" { "" } else { "," };
+    [
+        format!("{{{}:", serde_json::Value::from(key.as_str())).as_bytes(),
+        raw,
+        separator.as_bytes(),
+        rest.as_bytes(),
+    ]
+    .concat()
+}
+
+/// Generates malformed versions of the body, none for binary bodies, which
+/// have no format to break
+pub fn mutations_to_strategy(body: &Body) -> Option<BoxedStrategy<Body>> {
+    let bytes = body.to_bytes();
+    let mut mutations: Vec<BoxedStrategy<(Mutation, Vec<u8>)>> = vec![];
+
+    match &body.content {
+        Content::Binary(_) | Content::Raw { .. } => return None,
+        Content::Json(json) => {
+            if let Some((key, value)) = json.as_object().and_then(|object| object.iter().next()) {
+                let duplicate = if value.is_string() {
+                    serde_json::Value::from(0)
+                } else {
+                    serde_json::Value::from("duplicate")
+                };
+                let raw = format!(
+                    "{value},{}:{duplicate}",
+                    serde_json::Value::from(key.as_str())
+                );
+                mutations.push(
+                    Just((
+                        Mutation::DuplicateKey,
+                        with_first_value(json, raw.as_bytes()),
+                    ))
+                    .boxed(),
+                );
+            }
+            let nested = json.clone();
+            mutations.push(
+                select(NESTING_DEPTHS.to_vec())
+                    .prop_map(move |depth| {
+                        let raw = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+                        (
+                            Mutation::DeepNesting,
+                            with_first_value(&nested, raw.as_bytes()),
+                        )
+                    })
+                    .boxed(),
+            );
+            let huge = json.clone();
+            mutations.push(
+                select(huge_numbers())
+                    .prop_map(move |number| {
+                        (
+                            Mutation::HugeNumber,
+                            with_first_value(&huge, number.as_bytes()),
+                        )
+                    })
+                    .boxed(),
+            );
+            let invalid = json.clone();
+            mutations.push(
+                select(INVALID_UTF8)
+                    .prop_map(move |sequence| {
+                        let raw = [b"\"a", sequence, b"b\""].concat();
+                        (Mutation::InvalidUtf8, with_first_value(&invalid, &raw))
+                    })
+                    .boxed(),
+            );
+        }
+        _ => {
+            let invalid = bytes.clone();
+            mutations.push(
+                (0..=bytes.len(), select(INVALID_UTF8))
+                    .prop_map(move |(index, sequence)| {
+                        let mut bytes = invalid.clone();
+                        bytes.splice(index..index, sequence.iter().copied());
+                        (Mutation::InvalidUtf8, bytes)
+                    })
+                    .boxed(),
+            );
+        }
+    }
+
+    if !bytes.is_empty() {
+        let truncated = bytes.clone();
+        mutations.push(
+            (0..bytes.len())
+                .prop_map(move |length| (Mutation::Truncated, truncated[..length].to_vec()))
+                .boxed(),
+        );
+    }
+
+    let mut with_boms = vec![[b"\xef\xbb\xbf".as_slice(), &bytes].concat()];
+    if let Ok(text) = std::str::from_utf8(&bytes) {
+        let utf16 = |bom: [u8; 2], to_bytes: fn(u16) -> [u8; 2]| {
+            bom.iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(to_bytes))
+                .collect()
+        };
+        with_boms.push(utf16([0xff, 0xfe], u16::to_le_bytes));
+        with_boms.push(utf16([0xfe, 0xff], u16::to_be_bytes));
+    }
+    mutations.push(
+        select(with_boms)
+            .prop_map(|bytes| (Mutation::ByteOrderMark, bytes))
+            .boxed(),
+    );
+
+    let content_type = body.content_type.to_lowercase();
+    let foreign: Vec<_> = FOREIGN_BODIES
+        .iter()
+        .filter(|(format, _)| !content_type.contains(format))
+        .map(|(_, bytes)| bytes.to_vec())
+        .collect();
+    mutations.push(
+        select(foreign)
+            .prop_map(|bytes| (Mutation::MismatchedContent, bytes))
+            .boxed(),
+    );
+
+    let content_type = body.content_type.clone();
+    Some(
+        Union::new(mutations)
+            .prop_map(move |(mutation, data)| Body {
+                content_type: content_type.clone(),
+                content: Content::Raw { mutation, data },
+            })
+            .boxed(),
+    )
+}
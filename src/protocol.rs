@@ -0,0 +1,467 @@
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+    io::{self, ErrorKind, Read, Write},
+    net::TcpStream,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use proptest::{
+    option, prop_oneof,
+    sample::select,
+    strategy::{BoxedStrategy, Strategy},
+};
+use rustls::{ClientConfig, ClientConnection, ServerName, StreamOwned};
+use serde::{Deserialize, Serialize};
+
+/// How long to wait for a response before reporting the request as a hang.
+/// Tests wait for it against servers which never respond.
+const TIMEOUT: Duration = if cfg!(test) {
+    Duration::from_millis(500)
+} else {
+    Duration::from_secs(10)
+};
+
+/// Responses are read only up to this size, as only their status matters
+const MAX_RESPONSE_SIZE: usize = 1 << 20;
+
+/// Replaced by the length of the body in the values of `Content-Length`
+const LENGTH: &str = "{length}";
+
+const TRANSFER_ENCODINGS: &[&str] = &[
+    "chunked",
+    "Chunked",
+    " chunked",
+    "chunked, identity",
+    "identity, chunked",
+    "xchunked",
+    "chunked\t",
+];
+
+const CONTENT_LENGTHS: &[&[&str]] = &[
+    &[LENGTH, "0"],
+    &["0", LENGTH],
+    &["{length}, {length}"],
+    &[" {length}"],
+    &["-1"],
+    &["+0"],
+    &["0x10"],
+    &["abc"],
+    &["18446744073709551616"],
+];
+
+const METHODS: &[&str] = &[
+    "FOO", "PROPFIND", "TRACK", "DEBUG", "MOVE", "LOCK", "get", "post", "GETS",
+];
+
+const ACCEPT: &[&str] = &[
+    "application/xml",
+    "text/html",
+    "image/png",
+    "application/json; charset=ebcdic",
+    "application/json;q=abc",
+    "*/*;q=0",
+    "invalid",
+    "",
+];
+
+/// Deviation from a textbook HTTP/1.1 request. Requests with a mutation are
+/// written to the connection by hand, as HTTP clients refuse to send them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "kind")]
+pub enum ProtocolMutation {
+    /// `Content-Length` headers with values which are invalid, conflict with
+    /// each other or with the `Transfer-Encoding`. The body is chunked if
+    /// there is a `Transfer-Encoding`.
+    Framing {
+        content_length: Vec<String>,
+        transfer_encoding: Option<String>,
+    },
+    OversizedHeader {
+        length: usize,
+    },
+    ManyHeaders {
+        count: usize,
+    },
+    /// Second `Host` header
+    DuplicateHost {
+        host: String,
+    },
+    /// Method replacing the one of the operation (verb tampering)
+    Method {
+        method: String,
+    },
+    /// `Accept` header replacing the default one, none to leave it out
+    Accept {
+        value: Option<String>,
+    },
+}
+
+impl Display for ProtocolMutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolMutation::Framing {
+                content_length,
+                transfer_encoding: Some(transfer_encoding),
+            } => write!(
+                f,
+                "Content-Length {content_length:?} with Transfer-Encoding {transfer_encoding:?}"
+            ),
+            ProtocolMutation::Framing { content_length, .. } => {
+                write!(f, "Content-Length {content_length:?}")
+            }
+            ProtocolMutation::OversizedHeader { length } => write!(f, "header of {length} bytes"),
+            ProtocolMutation::ManyHeaders { count } => write!(f, "{count} headers"),
+            ProtocolMutation::DuplicateHost { host } => write!(f, "duplicate Host {host:?}"),
+            ProtocolMutation::Method { method } => write!(f, "method {method}"),
+            ProtocolMutation::Accept { value: Some(value) } => write!(f, "Accept {value:?}"),
+            ProtocolMutation::Accept { value: None } => f.write_str("missing Accept header"),
+        }
+    }
+}
+
+impl ProtocolMutation {
+    pub fn arbitrary() -> BoxedStrategy<ProtocolMutation> {
+        prop_oneof![
+            // Any Content-Length conflicts with a Transfer-Encoding
+            select(TRANSFER_ENCODINGS).prop_map(|transfer_encoding| ProtocolMutation::Framing {
+                content_length: vec![LENGTH.to_string()],
+                transfer_encoding: Some(transfer_encoding.to_string()),
+            }),
+            select(CONTENT_LENGTHS).prop_map(|content_length| ProtocolMutation::Framing {
+                content_length: content_length
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect(),
+                transfer_encoding: None,
+            }),
+            select(vec![1 << 13, 1 << 14, 1 << 16, 1 << 20])
+                .prop_map(|length| ProtocolMutation::OversizedHeader { length }),
+            select(vec![100, 1_000, 10_000])
+                .prop_map(|count| ProtocolMutation::ManyHeaders { count }),
+            select(vec!["localhost", "127.0.0.1", "example.com", ""]).prop_map(|host| {
+                ProtocolMutation::DuplicateHost {
+                    host: host.to_string(),
+                }
+            }),
+            select(METHODS).prop_map(|method| ProtocolMutation::Method {
+                method: method.to_string()
+            }),
+            option::of(select(ACCEPT)).prop_map(|value| ProtocolMutation::Accept {
+                value: value.map(String::from)
+            }),
+        ]
+        .boxed()
+    }
+}
+
+/// The server did not send any response to a mutated request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoResponse {
+    /// The connection was closed, which is a valid way to refuse the request
+    Closed,
+    Timeout,
+}
+
+impl Display for NoResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoResponse::Closed => f.write_str("connection closed without response"),
+            NoResponse::Timeout => write!(f, "no response in {TIMEOUT:?}"),
+        }
+    }
+}
+
+impl std::error::Error for NoResponse {}
+
+/// Writes the request prepared by ureq with the request target as it is, which
+/// the URL of the request would normalize, and the mutation applied. Errors
+/// with `NoResponse` if the server does not respond.
+pub fn send(
+    request: &ureq::Request,
+    target: &str,
+    body: Option<&[u8]>,
+    mutation: Option<&ProtocolMutation>,
+    tls_config: &Arc<ClientConfig>,
+) -> Result<ureq::Response> {
+    let url = request.request_url()?;
+    let url = url.as_url();
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("missing host in {url}"))?;
+    let address = url
+        .socket_addrs(|| None)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("unable to resolve {host}"))?;
+    let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let request = serialize(request, url, target, body.unwrap_or_default(), mutation);
+    let response = if url.scheme() == "https" {
+        let server_name =
+            ServerName::try_from(host).context(format!("invalid server name {host}"))?;
+        let connection = ClientConnection::new(tls_config.clone(), server_name)?;
+        exchange(StreamOwned::new(connection, stream), &request)
+    } else {
+        exchange(stream, &request)
+    };
+    let response = response?;
+
+    // Only the status line and the content type of the response are parsed,
+    // as the framing of the body does not need to be valid
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    let mut lines = head.lines();
+    let status_line = lines.next().unwrap_or_default();
+    let mut parts = status_line.splitn(3, ' ');
+    let (Some(_), Some(status)) = (parts.next(), parts.next()) else {
+        return Err(anyhow!("invalid status line {status_line:?}"));
+    };
+    let status = status
+        .parse()
+        .context(format!("invalid status line {status_line:?}"))?;
+    let status_text = parts.next().unwrap_or_default();
+    let content_type = lines.find(|line| {
+        line.split(':')
+            .next()
+            .is_some_and(|name| name.trim().eq_ignore_ascii_case("content-type"))
+    });
+    match content_type.and_then(|header| {
+        format!("HTTP/1.1 {status} {status_text}\r\n{header}\r\n\r\n{body}")
+            .parse()
+            .ok()
+    }) {
+        Some(response) => Ok(response),
+        None => ureq::Response::new(status, status_text, body).map_err(Into::into),
+    }
+}
+
+fn serialize(
+    request: &ureq::Request,
+    url: &url::Url,
+    target: &str,
+    body: &[u8],
+    mutation: Option<&ProtocolMutation>,
+) -> Vec<u8> {
+    let method = match mutation {
+        Some(ProtocolMutation::Method { method }) => method.as_str(),
+        _ => request.method(),
+    };
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+
+    let mut headers = vec![
+        ("Host".to_string(), host),
+        (
+            "User-Agent".to_string(),
+            concat!("openapi-fuzzer/", env!("CARGO_PKG_VERSION")).to_string(),
+        ),
+        ("Accept".to_string(), "*/*".to_string()),
+    ];
+    for name in request.header_names() {
+        headers.retain(|(header, _)| !header.eq_ignore_ascii_case(&name));
+        for value in request.all(&name) {
+            headers.push((name.clone(), value.to_string()));
+        }
+    }
+
+    let mut body = body.to_vec();
+    match mutation {
+        Some(ProtocolMutation::Framing {
+            content_length,
+            transfer_encoding,
+        }) => {
+            if let Some(transfer_encoding) = transfer_encoding {
+                let mut chunked = format!("{:x}\r\n", body.len()).into_bytes();
+                chunked.extend(&body);
+                chunked.extend(b"\r\n0\r\n\r\n");
+                body = chunked;
+                headers.push(("Transfer-Encoding".to_string(), transfer_encoding.clone()));
+            }
+            for value in content_length {
+                let value = value.replace(LENGTH, &body.len().to_string());
+                headers.push(("Content-Length".to_string(), value));
+            }
+        }
+        Some(ProtocolMutation::OversizedHeader { length }) => {
+            headers.push(("X-Oversized".to_string(), "A".repeat(*length)));
+        }
+        Some(ProtocolMutation::ManyHeaders { count }) => {
+            headers.extend((0..*count).map(|i| (format!("X-Header-{i}"), i.to_string())));
+        }
+        Some(ProtocolMutation::DuplicateHost { host }) => {
+            headers.insert(1, ("Host".to_string(), host.clone()));
+        }
+        Some(ProtocolMutation::Accept { value }) => {
+            headers.retain(|(header, _)| !header.eq_ignore_ascii_case("accept"));
+            if let Some(value) = value {
+                headers.push(("Accept".to_string(), value.clone()));
+            }
+        }
+        Some(ProtocolMutation::Method { .. }) | None => {}
+    }
+    if !matches!(mutation, Some(ProtocolMutation::Framing { .. })) && !body.is_empty() {
+        headers.push(("Content-Length".to_string(), body.len().to_string()));
+    }
+    headers.push(("Connection".to_string(), "close".to_string()));
+
+    let mut bytes = format!("{method} {target} HTTP/1.1\r\n").into_bytes();
+    for (name, value) in headers {
+        bytes.extend(format!("{name}: {value}\r\n").into_bytes());
+    }
+    bytes.extend(b"\r\n");
+    bytes.extend(body);
+    bytes
+}
+
+/// Sends the request and reads the response until the server closes the
+/// connection
+fn exchange(mut stream: impl Read + Write, request: &[u8]) -> Result<Vec<u8>> {
+    let is_closed = |error: &io::Error| {
+        matches!(
+            error.kind(),
+            ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe
+        )
+    };
+    let is_timeout =
+        |error: &io::Error| matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut);
+
+    // The server may respond before reading the whole request, e.g. when
+    // refusing oversized headers, so the response is read even if the write fails
+    if let Err(error) = stream.write_all(request).and_then(|_| stream.flush()) {
+        if !is_closed(&error) && !is_timeout(&error) {
+            return Err(error.into());
+        }
+    }
+
+    let started = Instant::now();
+    let mut response = vec![];
+    let mut buffer = [0; 8192];
+    while response.len() < MAX_RESPONSE_SIZE && started.elapsed() < TIMEOUT {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => response.extend(&buffer[..read]),
+            Err(error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) if is_closed(&error) || is_timeout(&error) => break,
+            // TLS connections closed without notifying the client
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    match response.windows(4).any(|window| window == b"\r\n\r\n") {
+        true => Ok(response),
+        false if started.elapsed() >= TIMEOUT => Err(NoResponse::Timeout.into()),
+        false if response.is_empty() => Err(NoResponse::Closed.into()),
+        false => Ok(response),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{net::TcpListener, thread};
+
+    use crate::verifier;
+
+    /// Sends a request with a mutation to the server, which handles the
+    /// connection with `respond`
+    fn send_to(respond: impl FnOnce(TcpStream) + Send + 'static) -> Result<ureq::Response> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/items", listener.local_addr().unwrap());
+        let server = thread::spawn(move || respond(listener.accept().unwrap().0));
+        let response = send(
+            &ureq::post(&url),
+            "/items",
+            Some(b"{}"),
+            Some(&ProtocolMutation::Method {
+                method: "FOO".to_string(),
+            }),
+            &Arc::new(verifier::skip_tls_verification_config()),
+        );
+        server.join().unwrap();
+        response
+    }
+
+    /// Reads the request up to the end of its headers
+    fn read_head(stream: &mut TcpStream) -> String {
+        let mut head = vec![];
+        let mut byte = [0];
+        while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap() == 1 {
+            head.push(byte[0]);
+        }
+        String::from_utf8(head).unwrap()
+    }
+
+    #[test]
+    fn test_responses() {
+        // Only the status line and the content type are parsed, the framing of
+        // the body is not checked
+        let response = send_to(|mut stream| {
+            assert!(read_head(&mut stream).starts_with("FOO /items HTTP/1.1\r\n"));
+            stream
+                .write_all(
+                    b"HTTP/1.1 501 Not Implemented\r\nContent-Length: 99\r\n\
+                      Content-Type: text/html\r\n\r\nFOO",
+                )
+                .unwrap();
+        })
+        .unwrap();
+        assert_eq!(response.status(), 501);
+        assert_eq!(response.status_text(), "Not Implemented");
+        assert_eq!(response.content_type(), "text/html");
+        assert_eq!(response.into_string().unwrap(), "FOO");
+
+        let error = send_to(drop).unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&NoResponse::Closed));
+
+        // The connection is held open until the client gives up
+        let error = send_to(|mut stream| {
+            read_head(&mut stream);
+            stream.read_to_end(&mut vec![]).unwrap();
+        })
+        .unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&NoResponse::Timeout));
+    }
+
+    #[test]
+    fn test_framing() {
+        let request = ureq::post("http://localhost:8080/items?id=1").set("X-Token", "a");
+        let url = request.request_url().unwrap();
+        let mutation = ProtocolMutation::Framing {
+            content_length: vec![LENGTH.to_string(), "0".to_string()],
+            transfer_encoding: Some("chunked".to_string()),
+        };
+
+        let request = serialize(
+            &request,
+            url.as_url(),
+            "/items?id=1",
+            b"{}",
+            Some(&mutation),
+        );
+        assert_eq!(
+            String::from_utf8(request).unwrap(),
+            format!(
+                "POST /items?id=1 HTTP/1.1\r\n\
+                 Host: localhost:8080\r\n\
+                 User-Agent: openapi-fuzzer/{}\r\n\
+                 Accept: */*\r\n\
+                 x-token: a\r\n\
+                 Transfer-Encoding: chunked\r\n\
+                 Content-Length: 12\r\n\
+                 Content-Length: 0\r\n\
+                 Connection: close\r\n\
+                 \r\n\
+                 2\r\n{{}}\r\n0\r\n\r\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+}
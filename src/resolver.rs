@@ -0,0 +1,129 @@
+use indexmap::IndexMap;
+use openapiv3::{
+    AnySchema, Components, Example, Parameter, ReferenceOr, RequestBody, Response, Schema,
+    SchemaData, SchemaKind,
+};
+
+use crate::xml::XmlHints;
+
+/// Resolves local references (`#/components/...`) of the specification.
+///
+/// References are resolved lazily while generating payloads, so that the names
+/// of the referenced schemas are known and cyclic schemas do not need to be
+/// expanded ahead of time.
+#[derive(Debug)]
+pub struct Resolver {
+    components: Components,
+    any_schema: Schema,
+    xml_hints: XmlHints,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Resolver::new(None)
+    }
+}
+
+impl Resolver {
+    pub fn new(components: Option<Components>) -> Self {
+        Resolver {
+            components: components.unwrap_or_default(),
+            any_schema: Schema {
+                schema_data: SchemaData::default(),
+                schema_kind: SchemaKind::Any(AnySchema::default()),
+            },
+            xml_hints: XmlHints::default(),
+        }
+    }
+
+    pub fn with_xml_hints(self, xml_hints: XmlHints) -> Self {
+        Resolver { xml_hints, ..self }
+    }
+
+    pub fn xml_hints(&self) -> &XmlHints {
+        &self.xml_hints
+    }
+
+    /// Unresolvable references, e.g. to other files, resolve to a schema
+    /// accepting anything, so that the operation can still be fuzzed
+    pub fn schema<'a>(&'a self, schema: &'a ReferenceOr<Schema>) -> &'a Schema {
+        match schema {
+            ReferenceOr::Item(schema) => schema,
+            ReferenceOr::Reference { reference } => self.schema_reference(reference),
+        }
+    }
+
+    pub fn boxed_schema<'a>(&'a self, schema: &'a ReferenceOr<Box<Schema>>) -> &'a Schema {
+        match schema {
+            ReferenceOr::Item(schema) => schema,
+            ReferenceOr::Reference { reference } => self.schema_reference(reference),
+        }
+    }
+
+    pub fn schema_reference(&self, reference: &str) -> &Schema {
+        resolve(&self.components.schemas, "schemas", reference).unwrap_or(&self.any_schema)
+    }
+
+    pub fn parameter<'a>(&'a self, parameter: &'a ReferenceOr<Parameter>) -> Option<&'a Parameter> {
+        match parameter {
+            ReferenceOr::Item(parameter) => Some(parameter),
+            ReferenceOr::Reference { reference } => {
+                resolve(&self.components.parameters, "parameters", reference)
+            }
+        }
+    }
+
+    pub fn request_body<'a>(
+        &'a self,
+        request_body: &'a ReferenceOr<RequestBody>,
+    ) -> Option<&'a RequestBody> {
+        match request_body {
+            ReferenceOr::Item(request_body) => Some(request_body),
+            ReferenceOr::Reference { reference } => {
+                resolve(&self.components.request_bodies, "requestBodies", reference)
+            }
+        }
+    }
+
+    pub fn example<'a>(&'a self, example: &'a ReferenceOr<Example>) -> Option<&'a Example> {
+        match example {
+            ReferenceOr::Item(example) => Some(example),
+            ReferenceOr::Reference { reference } => {
+                resolve(&self.components.examples, "examples", reference)
+            }
+        }
+    }
+
+    pub fn response<'a>(&'a self, response: &'a ReferenceOr<Response>) -> Option<&'a Response> {
+        match response {
+            ReferenceOr::Item(response) => Some(response),
+            ReferenceOr::Reference { reference } => {
+                resolve(&self.components.responses, "responses", reference)
+            }
+        }
+    }
+}
+
+/// Returns the name of the component the reference points to
+pub fn component_name(reference: &str) -> &str {
+    reference.rsplit('/').next().unwrap_or(reference)
+}
+
+fn resolve<'a: 'b, 'b, T>(
+    components: &'a IndexMap<String, ReferenceOr<T>>,
+    kind: &str,
+    mut reference: &'b str,
+) -> Option<&'a T> {
+    // Follow references to references, giving up on cycles
+    for _ in 0..=components.len() {
+        let name = reference
+            .strip_prefix("#/components/")?
+            .strip_prefix(kind)?
+            .strip_prefix('/')?;
+        match components.get(name)? {
+            ReferenceOr::Item(item) => return Some(item),
+            ReferenceOr::Reference { reference: next } => reference = next,
+        }
+    }
+    None
+}
@@ -0,0 +1,663 @@
+use std::convert::TryFrom;
+
+use indexmap::IndexSet;
+use openapiv3::{
+    AdditionalProperties, AnySchema, ArrayType, IntegerType, NumberType, ObjectType, ReferenceOr,
+    Schema, SchemaData, SchemaKind, StringType, Type, VariantOrUnknownOrEmpty,
+};
+
+use crate::resolver::Resolver;
+
+/// Returns JSON pointers to the properties of `value` which are write-only
+/// according to the `schema`
+pub fn write_only_properties(
+    resolver: &Resolver,
+    schema: &Schema,
+    value: &serde_json::Value,
+) -> Vec<String> {
+    // Branches of unions may find the same properties
+    let mut found = IndexSet::new();
+    find_write_only(resolver, schema, value, "", &mut found);
+    found.into_iter().collect()
+}
+
+fn find_write_only(
+    resolver: &Resolver,
+    schema: &Schema,
+    value: &serde_json::Value,
+    pointer: &str,
+    found: &mut IndexSet<String>,
+) {
+    match (&schema.schema_kind, value) {
+        (SchemaKind::AllOf { all_of }, _) => find_write_only(
+            resolver,
+            &merge_all_of(resolver, all_of),
+            value,
+            pointer,
+            found,
+        ),
+        (SchemaKind::OneOf { one_of: schemas }, _) | (SchemaKind::AnyOf { any_of: schemas }, _) => {
+            for schema in schemas {
+                find_write_only(resolver, resolver.schema(schema), value, pointer, found);
+            }
+        }
+        (
+            SchemaKind::Type(Type::Object(ObjectType {
+                properties,
+                additional_properties,
+                ..
+            }))
+            | SchemaKind::Any(AnySchema {
+                properties,
+                additional_properties,
+                ..
+            }),
+            serde_json::Value::Object(object),
+        ) => {
+            for (name, value) in object {
+                let schema = match (properties.get(name), additional_properties) {
+                    (Some(property), _) => resolver.boxed_schema(property),
+                    (None, Some(AdditionalProperties::Schema(schema))) => resolver.schema(schema),
+                    _ => continue,
+                };
+                let pointer = format!("{pointer}/{}", name.replace('~', "~0").replace('/', "~1"));
+                if schema.schema_data.write_only {
+                    found.insert(pointer);
+                } else {
+                    find_write_only(resolver, schema, value, &pointer, found);
+                }
+            }
+        }
+        (SchemaKind::Type(Type::Array(array)), serde_json::Value::Array(items)) => {
+            let schema = resolver.boxed_schema(&array.items);
+            for (index, item) in items.iter().enumerate() {
+                find_write_only(resolver, schema, item, &format!("{pointer}/{index}"), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Schema kind which no value satisfies, e.g. the merge of enumerations
+/// without a common value. It is a `oneOf` without any schema to match.
+pub fn unsatisfiable() -> SchemaKind {
+    SchemaKind::OneOf { one_of: vec![] }
+}
+
+/// Merges schemas of `allOf` into a single schema, which satisfies all of them
+pub fn merge_all_of(resolver: &Resolver, schemas: &[ReferenceOr<Schema>]) -> Schema {
+    merge_all(resolver, schemas, &mut vec![])
+}
+
+/// Merges the schemas like `merge_all_of`. The `merging` references are being
+/// merged already, so the schemas referring back to them are left out, as
+/// they would be merged over and over again.
+fn merge_all(
+    resolver: &Resolver,
+    schemas: &[ReferenceOr<Schema>],
+    merging: &mut Vec<String>,
+) -> Schema {
+    let flattened: Vec<_> = schemas
+        .iter()
+        .filter_map(|schema| {
+            merge_references(&[reference(schema)], merging, |merging| {
+                flatten_all_of(resolver, resolver.schema(schema), merging)
+            })
+        })
+        .collect();
+    flattened
+        .into_iter()
+        .reduce(|a, b| merge_schemas(resolver, a, b, merging))
+        .unwrap_or_else(|| Schema {
+            schema_data: SchemaData::default(),
+            schema_kind: SchemaKind::Any(AnySchema::default()),
+        })
+}
+
+/// Returns the reference of the schema, if it is one
+fn reference<T>(schema: &ReferenceOr<T>) -> Option<&str> {
+    match schema {
+        ReferenceOr::Reference { reference } => Some(reference),
+        ReferenceOr::Item(_) => None,
+    }
+}
+
+/// Runs the `merge` with the `references` added to the `merging` ones, unless
+/// some of them are being merged already
+fn merge_references<T>(
+    references: &[Option<&str>],
+    merging: &mut Vec<String>,
+    merge: impl FnOnce(&mut Vec<String>) -> T,
+) -> Option<T> {
+    let references: Vec<_> = references.iter().flatten().copied().collect();
+    if references
+        .iter()
+        .any(|reference| merging.iter().any(|merged| merged == reference))
+    {
+        return None;
+    }
+    let depth = merging.len();
+    merging.extend(references.iter().map(|reference| reference.to_string()));
+    let merged = merge(merging);
+    merging.truncate(depth);
+    Some(merged)
+}
+
+fn flatten_all_of(resolver: &Resolver, schema: &Schema, merging: &mut Vec<String>) -> Schema {
+    match &schema.schema_kind {
+        SchemaKind::AllOf { all_of } => {
+            let merged = merge_all(resolver, all_of, merging);
+            Schema {
+                schema_data: merge_schema_data(schema.schema_data.clone(), merged.schema_data),
+                schema_kind: merged.schema_kind,
+            }
+        }
+        _ => schema.clone(),
+    }
+}
+
+fn merge_schemas(resolver: &Resolver, a: Schema, b: Schema, merging: &mut Vec<String>) -> Schema {
+    Schema {
+        schema_data: merge_schema_data(a.schema_data, b.schema_data),
+        schema_kind: merge_schema_kinds(resolver, a.schema_kind, b.schema_kind, merging),
+    }
+}
+
+fn merge_schema_data(a: SchemaData, b: SchemaData) -> SchemaData {
+    SchemaData {
+        nullable: a.nullable && b.nullable,
+        read_only: a.read_only || b.read_only,
+        write_only: a.write_only || b.write_only,
+        deprecated: a.deprecated || b.deprecated,
+        external_docs: a.external_docs.or(b.external_docs),
+        example: a.example.or(b.example),
+        title: a.title.or(b.title),
+        description: a.description.or(b.description),
+        discriminator: a.discriminator.or(b.discriminator),
+        default: a.default.or(b.default),
+    }
+}
+
+fn merge_schema_kinds(
+    resolver: &Resolver,
+    a: SchemaKind,
+    b: SchemaKind,
+    merging: &mut Vec<String>,
+) -> SchemaKind {
+    match (a, b) {
+        (SchemaKind::AllOf { all_of }, other) | (other, SchemaKind::AllOf { all_of }) => {
+            let merged = merge_all(resolver, &all_of, merging);
+            merge_schema_kinds(resolver, merged.schema_kind, other, merging)
+        }
+        // Distribute the other schema into every branch, e.g. (A | B) & C becomes
+        // (A & C) | (B & C)
+        (SchemaKind::OneOf { one_of }, other) | (other, SchemaKind::OneOf { one_of }) => {
+            SchemaKind::OneOf {
+                one_of: distribute(resolver, one_of, other, merging),
+            }
+        }
+        (SchemaKind::AnyOf { any_of }, other) | (other, SchemaKind::AnyOf { any_of }) => {
+            SchemaKind::AnyOf {
+                any_of: distribute(resolver, any_of, other, merging),
+            }
+        }
+        (SchemaKind::Any(a), SchemaKind::Any(b)) => {
+            SchemaKind::Any(merge_any(resolver, a, b, merging))
+        }
+        (SchemaKind::Any(any), SchemaKind::Type(schema_type))
+        | (SchemaKind::Type(schema_type), SchemaKind::Any(any)) => match any_to_object(any) {
+            Some(object) => merge_types(resolver, schema_type, Type::Object(object), merging)
+                .map_or_else(unsatisfiable, SchemaKind::Type),
+            None => SchemaKind::Type(schema_type),
+        },
+        (SchemaKind::Type(a), SchemaKind::Type(b)) => {
+            merge_types(resolver, a, b, merging).map_or_else(unsatisfiable, SchemaKind::Type)
+        }
+    }
+}
+
+fn distribute(
+    resolver: &Resolver,
+    branches: Vec<ReferenceOr<Schema>>,
+    other: SchemaKind,
+    merging: &mut Vec<String>,
+) -> Vec<ReferenceOr<Schema>> {
+    branches
+        .into_iter()
+        .map(|branch| {
+            let merged = merge_references(&[reference(&branch)], merging, |merging| {
+                let flattened = flatten_all_of(resolver, resolver.schema(&branch), merging);
+                Schema {
+                    schema_data: flattened.schema_data,
+                    schema_kind: merge_schema_kinds(
+                        resolver,
+                        flattened.schema_kind,
+                        other.clone(),
+                        merging,
+                    ),
+                }
+            });
+            // Branches referring back to a merged schema are kept as they are
+            merged.map_or(branch, ReferenceOr::Item)
+        })
+        .collect()
+}
+
+/// Interprets schemas without a type which describe properties as objects
+fn any_to_object(any: AnySchema) -> Option<ObjectType> {
+    (!any.properties.is_empty() || !any.required.is_empty() || any.additional_properties.is_some())
+        .then(|| ObjectType {
+            properties: any.properties,
+            required: any.required,
+            additional_properties: any.additional_properties,
+            min_properties: any.min_properties,
+            max_properties: any.max_properties,
+        })
+}
+
+fn merge_any(
+    resolver: &Resolver,
+    mut a: AnySchema,
+    b: AnySchema,
+    merging: &mut Vec<String>,
+) -> AnySchema {
+    for (name, property) in b.properties {
+        merge_property(resolver, &mut a.properties, name, property, merging);
+    }
+    merge_required(&mut a.required, b.required);
+    a.additional_properties =
+        merge_additional_properties(a.additional_properties, b.additional_properties);
+    a.pattern = a.pattern.or(b.pattern);
+    a.format = a.format.or(b.format);
+    a.multiple_of = a.multiple_of.or(b.multiple_of);
+    a.minimum = max_option(a.minimum, b.minimum);
+    a.maximum = min_option(a.maximum, b.maximum);
+    a.min_items = max_option(a.min_items, b.min_items);
+    a.max_items = min_option(a.max_items, b.max_items);
+    a.min_properties = max_option(a.min_properties, b.min_properties);
+    a.max_properties = min_option(a.max_properties, b.max_properties);
+    a.items = a.items.or(b.items);
+    a
+}
+
+/// Merges two types. The first type is kept if they are not compatible, as no
+/// value can satisfy both of them. Returns none if their enumerations have no
+/// common value.
+fn merge_types(resolver: &Resolver, a: Type, b: Type, merging: &mut Vec<String>) -> Option<Type> {
+    let merged = match (a, b) {
+        (Type::Object(a), Type::Object(b)) => Type::Object(merge_objects(resolver, a, b, merging)),
+        (Type::String(a), Type::String(b)) => Type::String(merge_strings(a, b)?),
+        (Type::Integer(a), Type::Integer(b)) => Type::Integer(merge_integers(a, b)?),
+        (Type::Number(a), Type::Number(b)) => Type::Number(merge_numbers(a, b)?),
+        (Type::Array(a), Type::Array(b)) => Type::Array(merge_arrays(resolver, a, b, merging)),
+        (a, _) => a,
+    };
+    Some(merged)
+}
+
+fn merge_property(
+    resolver: &Resolver,
+    properties: &mut indexmap::IndexMap<String, ReferenceOr<Box<Schema>>>,
+    name: String,
+    property: ReferenceOr<Box<Schema>>,
+    merging: &mut Vec<String>,
+) {
+    let merged = match properties.remove(&name) {
+        Some(existing) => merge_boxed(resolver, existing, property, merging),
+        None => property,
+    };
+    properties.insert(name, merged);
+}
+
+/// Merges the schemas of properties or items. The first schema is kept if
+/// either of them refers back to a schema being merged.
+fn merge_boxed(
+    resolver: &Resolver,
+    a: ReferenceOr<Box<Schema>>,
+    b: ReferenceOr<Box<Schema>>,
+    merging: &mut Vec<String>,
+) -> ReferenceOr<Box<Schema>> {
+    let merged = merge_references(&[reference(&a), reference(&b)], merging, |merging| {
+        let flattened_a = flatten_all_of(resolver, resolver.boxed_schema(&a), merging);
+        let flattened_b = flatten_all_of(resolver, resolver.boxed_schema(&b), merging);
+        merge_schemas(resolver, flattened_a, flattened_b, merging)
+    });
+    merged.map_or(a, ReferenceOr::boxed_item)
+}
+
+fn merge_required(a: &mut Vec<String>, b: Vec<String>) {
+    for name in b {
+        if !a.contains(&name) {
+            a.push(name);
+        }
+    }
+}
+
+fn merge_additional_properties(
+    a: Option<AdditionalProperties>,
+    b: Option<AdditionalProperties>,
+) -> Option<AdditionalProperties> {
+    match (a, b) {
+        (Some(AdditionalProperties::Any(false)), _)
+        | (_, Some(AdditionalProperties::Any(false))) => Some(AdditionalProperties::Any(false)),
+        (a, b) => b.or(a),
+    }
+}
+
+fn merge_objects(
+    resolver: &Resolver,
+    mut a: ObjectType,
+    b: ObjectType,
+    merging: &mut Vec<String>,
+) -> ObjectType {
+    for (name, property) in b.properties {
+        merge_property(resolver, &mut a.properties, name, property, merging);
+    }
+    merge_required(&mut a.required, b.required);
+    a.additional_properties =
+        merge_additional_properties(a.additional_properties, b.additional_properties);
+    a.min_properties = max_option(a.min_properties, b.min_properties);
+    a.max_properties = min_option(a.max_properties, b.max_properties);
+    a
+}
+
+fn merge_strings(a: StringType, b: StringType) -> Option<StringType> {
+    Some(StringType {
+        format: match a.format {
+            VariantOrUnknownOrEmpty::Empty => b.format,
+            format => format,
+        },
+        pattern: a.pattern.or(b.pattern),
+        enumeration: merge_enumerations(a.enumeration, b.enumeration)?,
+        min_length: max_option(a.min_length, b.min_length),
+        max_length: min_option(a.max_length, b.max_length),
+    })
+}
+
+fn merge_integers(a: IntegerType, b: IntegerType) -> Option<IntegerType> {
+    let (minimum, exclusive_minimum) = match (a.minimum, b.minimum) {
+        (Some(a_min), Some(b_min)) if b_min > a_min => (b.minimum, b.exclusive_minimum),
+        (None, _) => (b.minimum, b.exclusive_minimum),
+        _ => (a.minimum, a.exclusive_minimum),
+    };
+    let (maximum, exclusive_maximum) = match (a.maximum, b.maximum) {
+        (Some(a_max), Some(b_max)) if b_max < a_max => (b.maximum, b.exclusive_maximum),
+        (None, _) => (b.maximum, b.exclusive_maximum),
+        _ => (a.maximum, a.exclusive_maximum),
+    };
+    let mut enumeration = merge_enumerations(a.enumeration, b.enumeration)?;
+    let multiple_of = match (a.multiple_of, b.multiple_of) {
+        (Some(a), Some(b)) if a != 0 && b != 0 => match lcm(a, b) {
+            Some(lcm) => Some(lcm),
+            // Zero is the only common multiple within 64 bits
+            None => {
+                enumeration = merge_enumerations(enumeration, vec![0])?;
+                Some(a.max(b))
+            }
+        },
+        (a, b) => a.or(b),
+    };
+    Some(IntegerType {
+        format: match a.format {
+            VariantOrUnknownOrEmpty::Empty => b.format,
+            format => format,
+        },
+        multiple_of,
+        exclusive_minimum,
+        exclusive_maximum,
+        minimum,
+        maximum,
+        enumeration,
+    })
+}
+
+fn merge_numbers(a: NumberType, b: NumberType) -> Option<NumberType> {
+    let (minimum, exclusive_minimum) = match (a.minimum, b.minimum) {
+        (Some(a_min), Some(b_min)) if b_min > a_min => (b.minimum, b.exclusive_minimum),
+        (None, _) => (b.minimum, b.exclusive_minimum),
+        _ => (a.minimum, a.exclusive_minimum),
+    };
+    let (maximum, exclusive_maximum) = match (a.maximum, b.maximum) {
+        (Some(a_max), Some(b_max)) if b_max < a_max => (b.maximum, b.exclusive_maximum),
+        (None, _) => (b.maximum, b.exclusive_maximum),
+        _ => (a.maximum, a.exclusive_maximum),
+    };
+    Some(NumberType {
+        format: match a.format {
+            VariantOrUnknownOrEmpty::Empty => b.format,
+            format => format,
+        },
+        multiple_of: a.multiple_of.or(b.multiple_of),
+        exclusive_minimum,
+        exclusive_maximum,
+        minimum,
+        maximum,
+        enumeration: merge_enumerations(a.enumeration, b.enumeration)?,
+    })
+}
+
+fn merge_arrays(
+    resolver: &Resolver,
+    a: ArrayType,
+    b: ArrayType,
+    merging: &mut Vec<String>,
+) -> ArrayType {
+    ArrayType {
+        items: merge_boxed(resolver, a.items, b.items, merging),
+        min_items: max_option(a.min_items, b.min_items),
+        max_items: min_option(a.max_items, b.max_items),
+        unique_items: a.unique_items || b.unique_items,
+    }
+}
+
+/// Keeps only values allowed by both enumerations. An empty enumeration allows
+/// everything, so the other one is used. Returns none if no value is allowed
+/// by both.
+fn merge_enumerations<T: PartialEq>(a: Vec<T>, b: Vec<T>) -> Option<Vec<T>> {
+    if a.is_empty() {
+        return Some(b);
+    }
+    if b.is_empty() {
+        return Some(a);
+    }
+    let merged: Vec<_> = a.into_iter().filter(|value| b.contains(value)).collect();
+    (!merged.is_empty()).then_some(merged)
+}
+
+fn max_option<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b > a { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+fn min_option<T: PartialOrd>(a: Option<T>, b: Option<T>) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if b < a { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Returns the least common multiple, none if it does not fit into `i64`
+fn lcm(a: i64, b: i64) -> Option<i64> {
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+    let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+    (a / gcd(a, b))
+        .checked_mul(b)
+        .and_then(|lcm| i64::try_from(lcm).ok())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn schema(schema_type: Type) -> ReferenceOr<Schema> {
+        ReferenceOr::Item(Schema {
+            schema_data: SchemaData::default(),
+            schema_kind: SchemaKind::Type(schema_type),
+        })
+    }
+
+    #[test]
+    fn test_write_only_properties() {
+        let resolver = Resolver::new(None);
+        let boxed = |write_only, schema_type| {
+            ReferenceOr::boxed_item(Schema {
+                schema_data: SchemaData {
+                    write_only,
+                    ..Default::default()
+                },
+                schema_kind: SchemaKind::Type(schema_type),
+            })
+        };
+        let users = |name: &str| {
+            let user = Type::Object(ObjectType {
+                properties: vec![
+                    (
+                        name.to_string(),
+                        boxed(false, Type::String(Default::default())),
+                    ),
+                    (
+                        "password".to_string(),
+                        boxed(true, Type::String(Default::default())),
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            });
+            schema(Type::Array(ArrayType {
+                items: boxed(false, user),
+                min_items: None,
+                max_items: None,
+                unique_items: false,
+            }))
+        };
+        // Both branches of the union find the same properties
+        let schema = Schema {
+            schema_data: SchemaData::default(),
+            schema_kind: SchemaKind::OneOf {
+                one_of: vec![users("name"), users("email")],
+            },
+        };
+
+        let value = serde_json::json!([
+            {"name": "a", "password": "secret"},
+            {"name": "b"},
+            {"email": "c", "password": "secret"},
+        ]);
+        assert_eq!(
+            write_only_properties(&resolver, &schema, &value),
+            ["/0/password", "/2/password"]
+        );
+    }
+
+    #[test]
+    fn test_merge_enumerations() {
+        let resolver = Resolver::new(None);
+        let string = |enumeration: &[&str]| {
+            schema(Type::String(StringType {
+                enumeration: enumeration.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            }))
+        };
+
+        let merged = merge_all_of(&resolver, &[string(&["a", "b"]), string(&["b", "c"])]);
+        assert_eq!(
+            merged.schema_kind,
+            SchemaKind::Type(Type::String(StringType {
+                enumeration: vec!["b".to_string()],
+                ..Default::default()
+            }))
+        );
+        let merged = merge_all_of(&resolver, &[string(&["a"]), string(&["c"])]);
+        assert_eq!(merged.schema_kind, unsatisfiable());
+    }
+
+    #[test]
+    fn test_merge_multiple_of() {
+        let resolver = Resolver::new(None);
+        let integer = |multiple_of| {
+            schema(Type::Integer(IntegerType {
+                multiple_of: Some(multiple_of),
+                ..Default::default()
+            }))
+        };
+        let merged = |a, b| match merge_all_of(&resolver, &[integer(a), integer(b)]).schema_kind {
+            SchemaKind::Type(Type::Integer(integer)) => (integer.multiple_of, integer.enumeration),
+            schema_kind => panic!("Unexpected schema: {:?}", schema_kind),
+        };
+
+        assert_eq!(merged(4, -6), (Some(12), vec![]));
+        assert_eq!(merged(i64::MAX, i64::MAX - 1), (Some(i64::MAX), vec![0]));
+        assert_eq!(merged(i64::MIN, 3), (Some(3), vec![0]));
+    }
+
+    #[test]
+    fn test_merge_recursive_all_of() {
+        let components: openapiv3::Components = serde_yaml::from_str(
+            r##"
+            schemas:
+              Node:
+                allOf:
+                  - $ref: "#/components/schemas/Base"
+                  - type: object
+                    properties:
+                      parent: {$ref: "#/components/schemas/Node"}
+                      children:
+                        type: array
+                        items: {$ref: "#/components/schemas/Node"}
+              Base:
+                type: object
+                properties:
+                  id: {type: integer}
+                  parent: {$ref: "#/components/schemas/Node"}
+                  children:
+                    type: array
+                    maxItems: 3
+                    items: {$ref: "#/components/schemas/Node"}
+              Loop:
+                allOf:
+                  - $ref: "#/components/schemas/Loop"
+                  - {type: string, minLength: 1}
+            "##,
+        )
+        .unwrap();
+        let resolver = Resolver::new(Some(components));
+        let all_of = |name: &str| match &resolver.schema_reference(name).schema_kind {
+            SchemaKind::AllOf { all_of } => merge_all_of(&resolver, all_of).schema_kind,
+            schema_kind => panic!("Unexpected schema: {:?}", schema_kind),
+        };
+
+        let SchemaKind::Type(Type::Object(node)) = all_of("#/components/schemas/Node") else {
+            panic!("Node is not an object");
+        };
+        assert_eq!(
+            node.properties.keys().collect::<Vec<_>>(),
+            ["id", "parent", "children"]
+        );
+        let children = resolver.boxed_schema(&node.properties["children"]);
+        assert!(matches!(
+            &children.schema_kind,
+            SchemaKind::Type(Type::Array(ArrayType {
+                max_items: Some(3),
+                ..
+            }))
+        ));
+
+        // The schema referring to itself adds no constraints
+        assert_eq!(
+            all_of("#/components/schemas/Loop"),
+            SchemaKind::Type(Type::String(StringType {
+                min_length: Some(1),
+                ..Default::default()
+            }))
+        );
+    }
+}
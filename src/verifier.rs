@@ -20,6 +20,21 @@ impl ServerCertVerifier for SkipTlsVerification {
     }
 }
 
+/// Verifies certificates against the certificates of the platform, like ureq
+/// does
+pub fn native_certs_config() -> rustls::ClientConfig {
+    let mut roots = rustls::RootCertStore::empty();
+    // Native stores often include certificates which cannot be parsed, so
+    // they are skipped
+    for certificate in rustls_native_certs::load_native_certs().unwrap_or_default() {
+        let _ = roots.add(&rustls::Certificate(certificate.0));
+    }
+    rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth()
+}
+
 pub fn skip_tls_verification_config() -> rustls::ClientConfig {
     rustls::ClientConfig::builder()
         .with_safe_defaults()
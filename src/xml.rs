@@ -0,0 +1,230 @@
+use indexmap::IndexMap;
+use openapiv3::{ReferenceOr, Schema, SchemaKind, Type};
+use serde::Deserialize;
+
+use crate::resolver::{component_name, Resolver};
+
+/// XML hints of the component schemas.
+///
+/// The `xml` object is not parsed by openapiv3, so the hints are read from the
+/// specification separately. Hints of inline request body schemas are not
+/// supported, only of the component schemas and the schemas nested in them.
+#[derive(Debug, Default, Deserialize)]
+pub struct XmlHints {
+    #[serde(default)]
+    components: ComponentHints,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ComponentHints {
+    #[serde(default)]
+    schemas: IndexMap<String, SchemaHints>,
+}
+
+/// The `xml` object of a schema together with the ones of its nested schemas
+#[derive(Debug, Default, Deserialize)]
+pub struct SchemaHints {
+    #[serde(default)]
+    xml: Xml,
+    #[serde(default)]
+    properties: IndexMap<String, SchemaHints>,
+    items: Option<Box<SchemaHints>>,
+}
+
+/// https://spec.openapis.org/oas/v3.0.3#xml-object
+#[derive(Debug, Default, Deserialize)]
+struct Xml {
+    name: Option<String>,
+    namespace: Option<String>,
+    prefix: Option<String>,
+    #[serde(default)]
+    attribute: bool,
+    #[serde(default)]
+    wrapped: bool,
+}
+
+impl XmlHints {
+    /// Reads the hints from the specification. Hints which cannot be parsed
+    /// are ignored, as the body can be rendered without them.
+    pub fn from_specification(specification: &str) -> Self {
+        serde_yaml::from_str(specification).unwrap_or_default()
+    }
+
+    pub fn schema(&self, name: &str) -> Option<&SchemaHints> {
+        self.components.schemas.get(name)
+    }
+}
+
+/// Renders a JSON value generated for the schema as an XML document. The root
+/// element is named after the referenced schema.
+pub fn to_xml(
+    resolver: &Resolver,
+    schema: &ReferenceOr<Schema>,
+    value: &serde_json::Value,
+) -> String {
+    let (name, schema, hints) = match schema {
+        ReferenceOr::Reference { reference } => (
+            component_name(reference),
+            resolver.schema_reference(reference),
+            resolver.xml_hints().schema(component_name(reference)),
+        ),
+        ReferenceOr::Item(schema) => ("root", schema, None),
+    };
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    write_element(resolver, &mut xml, name, Some(schema), hints, value);
+    xml
+}
+
+fn write_element(
+    resolver: &Resolver,
+    xml: &mut String,
+    name: &str,
+    schema: Option<&Schema>,
+    hints: Option<&SchemaHints>,
+    value: &serde_json::Value,
+) {
+    let hint = hints.map(|hints| &hints.xml);
+    let property_name = name;
+    let (name, namespace) = qualified_name(hint, property_name);
+
+    match value {
+        serde_json::Value::Array(items) => {
+            let (items_schema, items_hints) = match schema.map(|schema| &schema.schema_kind) {
+                Some(SchemaKind::Type(Type::Array(array))) => resolve(
+                    resolver,
+                    &array.items,
+                    hints.and_then(|hints| hints.items.as_deref()),
+                ),
+                _ => (None, None),
+            };
+            // Items of unwrapped arrays are repeated in place of the array.
+            // Items are named by their own hints or after the property.
+            let wrapped = hint.is_some_and(|xml| xml.wrapped);
+            if wrapped {
+                xml.push_str(&format!("<{name}{namespace}>"));
+            }
+            for item in items {
+                write_element(
+                    resolver,
+                    xml,
+                    property_name,
+                    items_schema,
+                    items_hints,
+                    item,
+                );
+            }
+            if wrapped {
+                xml.push_str(&format!("</{name}>"));
+            }
+        }
+        serde_json::Value::Object(properties) => {
+            let mut attributes = namespace;
+            let mut children = String::new();
+            for (property_name, property_value) in properties {
+                let (property_schema, property_hints) = schema
+                    .and_then(|schema| property(resolver, schema, property_name))
+                    .map_or((None, None), |property| {
+                        resolve(
+                            resolver,
+                            property,
+                            hints.and_then(|hints| hints.properties.get(property_name)),
+                        )
+                    });
+                let is_attribute = property_hints.is_some_and(|hints| hints.xml.attribute);
+                match property_value {
+                    serde_json::Value::Array(_) | serde_json::Value::Object(_) if is_attribute => {}
+                    property_value if is_attribute => {
+                        let hint = property_hints.map(|hints| &hints.xml);
+                        let (attribute_name, namespace) = qualified_name(hint, property_name);
+                        // Attributes without a prefix are in no namespace
+                        if hint.is_some_and(|xml| xml.prefix.is_some()) {
+                            attributes.push_str(&namespace);
+                        }
+                        attributes.push_str(&format!(
+                            r#" {attribute_name}="{}""#,
+                            escape(&text(property_value))
+                        ));
+                    }
+                    property_value => write_element(
+                        resolver,
+                        &mut children,
+                        property_name,
+                        property_schema,
+                        property_hints,
+                        property_value,
+                    ),
+                }
+            }
+            xml.push_str(&format!("<{name}{attributes}>{children}</{name}>"));
+        }
+        serde_json::Value::Null => xml.push_str(&format!("<{name}{namespace}/>")),
+        value => xml.push_str(&format!(
+            "<{name}{namespace}>{}</{name}>",
+            escape(&text(value))
+        )),
+    }
+}
+
+/// Returns the name of the element or attribute with its prefix, together
+/// with the declaration of its namespace
+fn qualified_name(hint: Option<&Xml>, name: &str) -> (String, String) {
+    let name = hint.and_then(|xml| xml.name.as_deref()).unwrap_or(name);
+    let prefix = hint.and_then(|xml| xml.prefix.as_deref());
+    let namespace = hint
+        .and_then(|xml| xml.namespace.as_deref())
+        .map(|namespace| match prefix {
+            Some(prefix) => format!(r#" xmlns:{prefix}="{}""#, escape(namespace)),
+            None => format!(r#" xmlns="{}""#, escape(namespace)),
+        })
+        .unwrap_or_default();
+    match prefix {
+        Some(prefix) => (format!("{prefix}:{name}"), namespace),
+        None => (name.to_string(), namespace),
+    }
+}
+
+/// Resolves the schema together with its hints, which are the ones of the
+/// component in case of references
+fn resolve<'a>(
+    resolver: &'a Resolver,
+    schema: &'a ReferenceOr<Box<Schema>>,
+    hints: Option<&'a SchemaHints>,
+) -> (Option<&'a Schema>, Option<&'a SchemaHints>) {
+    match schema {
+        ReferenceOr::Reference { reference } => (
+            Some(resolver.schema_reference(reference)),
+            resolver.xml_hints().schema(component_name(reference)),
+        ),
+        ReferenceOr::Item(schema) => (Some(schema), hints),
+    }
+}
+
+fn property<'a>(
+    resolver: &'a Resolver,
+    schema: &'a Schema,
+    name: &str,
+) -> Option<&'a ReferenceOr<Box<Schema>>> {
+    match &schema.schema_kind {
+        SchemaKind::Type(Type::Object(object)) => object.properties.get(name),
+        SchemaKind::Any(any) => any.properties.get(name),
+        SchemaKind::AllOf { all_of } => all_of
+            .iter()
+            .find_map(|schema| property(resolver, resolver.schema(schema), name)),
+        _ => None,
+    }
+}
+
+fn text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}